@@ -0,0 +1,33 @@
+/// The clock the deadlines of a DAO or a crowdsale are measured with.
+use parity_codec::{Decode, Encode};
+use runtime_primitives::traits::As;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use timestamp;
+
+/// Unit of the deadlines and periods stored in `Moment` fields. The block authors can
+/// skew the timestamp a little, the block number can not be skewed.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub enum Clock {
+    /// The deadlines are `timestamp` moments.
+    Timestamp,
+    /// The deadlines are block numbers.
+    BlockNumber,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock::Timestamp
+    }
+}
+
+impl Clock {
+    /// The current time on this clock.
+    pub fn now<T: timestamp::Trait>(self) -> T::Moment {
+        match self {
+            Clock::Timestamp => <timestamp::Module<T>>::get(),
+            Clock::BlockNumber => T::Moment::sa(<system::Module<T>>::block_number().as_()),
+        }
+    }
+}
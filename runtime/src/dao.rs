@@ -1,72 +1,417 @@
 /// A simple implementation of the DAO.
+use crate::clock::Clock;
 use crate::daotoken as token;
+use crate::erc721;
+use crate::lockabletoken;
 use parity_codec::{Decode, Encode};
 use rstd::prelude::*;
+use rstd::result;
 use runtime_primitives::traits::{As, CheckedAdd, CheckedSub, Hash};
 use support::{
-    decl_event, decl_module, decl_storage, dispatch::Result, ensure, StorageMap, StorageValue,
+    decl_event, decl_module, decl_storage,
+    dispatch::{Dispatchable, Result},
+    ensure,
+    traits::Currency,
+    Parameter, StorageMap, StorageValue,
 };
-use {system::ensure_signed, timestamp};
+use system::{ensure_signed, EnsureOrigin};
+use timestamp;
 
 #[cfg(feature = "std")]
 use runtime_io::with_storage;
+#[cfg(feature = "std")]
+use serde::Serialize;
 
-pub trait Trait: timestamp::Trait + token::Trait {
+/// A DAO is governed either by the `daotoken` or by a `lockabletoken` asset, both
+/// must use the same balance type as the native currency held by the DAO treasuries.
+pub trait Trait:
+    timestamp::Trait
+    + token::Trait
+    + lockabletoken::Trait<TokenBalance = <Self as token::Trait>::TokenBalance>
+    + erc721::Trait
+{
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+    /// The outer origin type, a passed call proposal is dispatched with `RawOrigin::Dao`.
+    type Origin: From<RawOrigin>;
+    /// The outer call type a proposal can carry.
+    type Proposal: Parameter + Dispatchable<Origin = <Self as Trait>::Origin>;
+    /// Origin of the calls of this module made by a passed call proposal, it returns the
+    /// id of the DAO. The runtime sets it to `EnsureDao`.
+    type ExternalOrigin: EnsureOrigin<<Self as system::Trait>::Origin, Success = u64>;
+    /// The native currency of the DAO treasuries.
+    type Currency: Currency<Self::AccountId, Balance = BalanceOf<Self>>;
+}
+
+pub type BalanceOf<T> = <T as token::Trait>::TokenBalance;
+type DaoInfoOf<T> = DaoInfo<<T as system::Trait>::AccountId, BalanceOf<T>, <T as timestamp::Trait>::Moment>;
+type DaoParameterOf<T> = DaoParameter<BalanceOf<T>, <T as timestamp::Trait>::Moment>;
+type NftMembershipOf<T> = NftMembership<<T as system::Trait>::AccountId>;
+type BountyOf<T> = Bounty<<T as system::Trait>::AccountId, BalanceOf<T>, <T as timestamp::Trait>::Moment>;
+
+/// Maximum number of scheduled proposals executed in one block.
+const MAX_EXECUTIONS_PER_BLOCK: usize = 5;
+
+/// Maximum number of schedule times checked per clock in one block, the schedule catches
+/// up over the next blocks when the clock moves further.
+const MAX_SCHEDULE_SCAN: usize = 100;
+
+/// A conviction of 0 votes with a tenth of the balance and locks nothing. A conviction
+/// of n > 0 multiplies the balance by n and locks it for 2^(n-1) vote lock periods
+/// after the voting deadline.
+const MAX_CONVICTION: u8 = 6;
+
+/// Maximum number of open quadratic proposals an account votes on at the same time.
+const MAX_QUADRATIC_VOTES: usize = 16;
+
+/// Delegations are followed transitively up to this many hops from the voter.
+const MAX_DELEGATION_DEPTH: u32 = 5;
+
+/// Maximum number of accounts delegating directly to one account, for one topic.
+const MAX_DELEGATORS: usize = 16;
+
+/// Maximum number of delegators counted in the voting power of an account, the closest
+/// ones first.
+const MAX_DELEGATION_GRAPH: usize = 128;
+
+/// Maximum number of accounts in the curator council of a DAO.
+const MAX_COUNCIL_SIZE: usize = 16;
+
+/// Maximum number of items returned by one page of the query functions.
+const MAX_PAGE_SIZE: u64 = 100;
+
+/// The `lockabletoken` id of the token of a child DAO is its DAO id with this bit set, apart
+/// from the crowdsale tokens numbered from 0.
+const CHILD_TOKEN_BASE: u64 = 1 << 63;
+
+/// How the votes of a proposal are weighted.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize))]
+pub enum VotingMode {
+    /// The weight is the token balance scaled by the conviction, see `vote`.
+    Token,
+    /// The weight is the square root of the credits spent, see `vote_quadratic`.
+    Quadratic,
+    /// The votes are committed secretly and revealed after the debate, see `commit_vote`.
+    Secret,
+}
+
+impl Default for VotingMode {
+    fn default() -> Self {
+        VotingMode::Token
+    }
+}
+
+/// Voting rights of an NFT gated DAO, derived from the `erc721` tokens of its members.
+/// Only the tokens issued by the given account, the collection of the DAO, are counted.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum NftMembership<AccountId> {
+    /// One NFT, one vote.
+    PerToken(AccountId),
+    /// Each NFT weighs the value of this `erc721` attribute.
+    Attribute(AccountId, Vec<u8>),
+}
+
+impl<AccountId: Clone> NftMembership<AccountId> {
+    /// The issuer of the collection.
+    pub fn issuer(&self) -> AccountId {
+        match self {
+            NftMembership::PerToken(issuer) | NftMembership::Attribute(issuer, _) => issuer.clone(),
+        }
+    }
+}
+
+/// An action of the curator council, it is executed once `threshold` members approved it.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum CuratorAction<AccountId, Balance> {
+    ChangeMinProposalDeposit(Balance),
+    ChangeAllowedRecipient(AccountId, bool),
+    HalveMinQuorum,
+}
+
+/// A DAO parameter changed by a parameter proposal, see `new_parameter_proposal`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum DaoParameter<Balance, Moment> {
+    MinProposalDeposit(Balance),
+    MinQuorumDivisor(u64),
+    MinProposalDebatePeriod(Moment),
+    MaxProposalDebatePeriod(Moment),
+    QuorumHavlingPeriod(Moment),
+    ExecuteProposalPeriod(Moment),
+    MaxDepositDivisor(u64),
+    ParameterTimelock(Moment),
+    ChallengePeriod(Moment),
+    VetoDivisor(u64),
+    RewardDivisor(u64),
+    RewardClaimPeriod(Moment),
+}
+
+/// State of a bounty, see `new_bounty_proposal`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum BountyStatus<AccountId, Moment> {
+    /// The bounty proposal is not executed yet.
+    Proposed,
+    /// The budget is funded, the curator can assign the bounty.
+    Active,
+    Assigned(AccountId),
+    /// The contributor submitted its work for review.
+    Submitted(AccountId),
+    /// The curator accepted the work, the budget can be paid out from this time.
+    PendingPayout(AccountId, Moment),
+}
+
+/// Work done in `on_finalize` for a proposal, see `schedule`.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum ScheduledTask {
+    /// Execute, enact or apply the proposal, depending on its state.
+    Proposal(u64),
+    /// Return the rewards its voters did not claim to the reward pool.
+    ExpireRewards(u64),
 }
 
+impl<AccountId, Moment> Default for BountyStatus<AccountId, Moment> {
+    fn default() -> Self {
+        BountyStatus::Proposed
+    }
+}
+
+/// Origin for the DAO module.
+#[derive(PartialEq, Eq, Clone)]
 #[cfg_attr(feature = "std", derive(Debug))]
+pub enum RawOrigin {
+    /// The call was approved by a proposal of the token holders of the DAO with this id.
+    Dao(u64),
+}
+
+/// Origin for the DAO module.
+pub type Origin = RawOrigin;
+
+/// Ensure that the origin is a DAO, other modules can use it to accept calls
+/// passed by the token holders. It returns the id of the DAO.
+pub struct EnsureDao;
+impl<O: Into<Option<RawOrigin>>> EnsureOrigin<O> for EnsureDao {
+    type Success = u64;
+    fn ensure_origin(o: O) -> result::Result<Self::Success, &'static str> {
+        match o.into() {
+            Some(RawOrigin::Dao(dao_id)) => Ok(dao_id),
+            _ => Err("bad origin: expected to be a DAO origin"),
+        }
+    }
+}
+
+#[cfg_attr(feature = "std", derive(Debug, Serialize))]
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
 // generic type parameters - Balance, AccountId, timestamp::Moment, Hash
 pub struct Proposal<U, V, W, X> {
     // The address where the `amount` will go to if the proposal is accepted
-    recipient: V,
+    pub recipient: V,
     // The amount to transfer to `recipient` if the proposal is accepted.
-    amount: U,
-    description: Vec<u8>,
-    voting_deadline: W,
-    open: bool,
-    proposal_passed: bool,
-    proposal_hash: X,
+    pub amount: U,
+    pub description: Vec<u8>,
+    pub voting_deadline: W,
+    pub open: bool,
+    pub proposal_passed: bool,
+    pub proposal_hash: X,
     // Deposit in wei the creator added when submitting their proposal. It
     // is taken from the msg.value of a new_proposal call.
-    proposal_deposit: U,
-    new_curator: bool,
-    yea: U,
-    nay: U,
-    creator: V,
+    pub proposal_deposit: U,
+    pub new_curator: bool,
+    pub yea: U,
+    pub nay: U,
+    pub creator: V,
+}
+
+/// A proposal together with its tally, as returned by the `DaoApi` runtime API.
+#[cfg_attr(feature = "std", derive(Debug, Serialize))]
+#[derive(Encode, Decode, Clone, PartialEq)]
+pub struct ProposalInfo<AccountId, Balance, Moment, Hash> {
+    pub id: u64,
+    pub dao_id: u64,
+    pub proposal: Proposal<Balance, AccountId, Moment, Hash>,
+    pub voting_mode: VotingMode,
+    // Tokens behind the yes and no votes, before conviction or quadratic weighting
+    pub raw_yea: Balance,
+    pub raw_nay: Balance,
+    // Yes votes needed for the proposal to pass, see `execute_proposal`
+    pub quorum_required: Balance,
+    // Time left until the voting deadline, zero once it has passed
+    pub time_remaining: Moment,
+    // The proposal pays native currency from the treasury instead of tokens
+    pub pays_native: bool,
+    // End of the grace period of a passed proposal which is not executed yet
+    pub grace_period_end: Option<Moment>,
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+// generic type parameters - Balance
+// A DAO split off by the yes voters of a `new_curator` proposal.
+pub struct ChildDao<U> {
+    // The DAO created by the first split, the new curator is its curator
+    dao_id: u64,
+    // Funds, treasury and token supply of the parent DAO when the first member split,
+    // every member receives the same pro-rata share of them
+    split_balance: U,
+    split_treasury: U,
+    split_total_supply: U,
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+// generic type parameters - AccountId, Balance, timestamp::Moment
+// A task paid from the DAO treasury, its budget is held by the bounty account.
+pub struct Bounty<U, V, W> {
+    dao_id: u64,
+    // Assigns the bounty and reviews the work
+    curator: U,
+    budget: V,
+    // Time between the acceptance of the work and its payout
+    review_delay: W,
+    description: Vec<u8>,
+    status: BountyStatus<U, W>,
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+// generic type parameters - AccountId, Balance, timestamp::Moment
+// The curator, governance token and parameters of a DAO instance.
+pub struct DaoInfo<U, V, W> {
+    // The creator of the DAO, then the first member of the council set by a proposal. The
+    // funds of the DAO are held by its `treasury_account`
+    curator: U,
+    // `threshold` members of the council approve the curator actions
+    council: Vec<U>,
+    threshold: u32,
+    // `None` for the `daotoken`, otherwise the id of a `lockabletoken` asset
+    token: Option<u64>,
+    // With `Some`, the votes are weighted by the `erc721` tokens of the members rather
+    // than by the governance token, which is still used for the deposits
+    membership: Option<NftMembership<U>>,
+    // The deadlines and periods below are block numbers with `Clock::BlockNumber`
+    clock: Clock,
+    min_proposal_deposit: V,
+    min_quorum_divisor: u64,
+    min_proposal_debate_period: W,
+    max_proposal_debate_period: W,
+    quorum_havling_period: W,
+    execute_proposal_period: W,
+    max_deposit_divisor: u64,
+    // Percentage of the deposit sent to the treasury when a proposal misses the quorum
+    deposit_slash_percent: u32,
+    vote_lock_period: W,
+    // Time between a proposal passing and its execution, members can ragequit during it
+    grace_period: W,
+    // Time between a parameter proposal being executed and the change taking effect
+    parameter_timelock: W,
+    // An optimistic proposal passes after `challenge_period` unless the vetoes reach
+    // the token supply divided by `veto_divisor`
+    challenge_period: W,
+    veto_divisor: u64,
+    // A closing proposal with votes reserves the reward pool divided by `reward_divisor`
+    // for its voters, they can claim it during `reward_claim_period`
+    reward_divisor: u64,
+    reward_claim_period: W,
+    last_time_min_quorum_met: W,
+    sum_of_proposal_deposits: V,
+    proposal_count: u64,
 }
 
 // storage
 decl_storage! {
     trait Store for Module<T: Trait> as Dao {
-        // stores the curator in the genesis config
-        Curator get(curator) config(): Option<T::AccountId>;
-
-        VoteNo get(vote_no): map T::AccountId => bool;
-        VoteYes get(vote_yes): map T::AccountId => bool;
+        // DAO instances, the DAO 0 is governed by the `daotoken` and set in the genesis config
+        Daos get(daos): map u64 => Option<DaoInfoOf<T>>;
+        DaoCount get(dao_count): u64;
 
-        // DAO parameter begin
-        MinProposalDeposit get(min_proposal_deposit) config(): Option<T::TokenBalance>;
-        LastTimeMinQuorumMet get(last_time_min_quorum_met): Option<T::Moment>;
-        MinQuorumDivisor get(min_quorum_divisor) config(): Option<u64>;
-        MinProposalDebatePeriod get(min_proposal_debate_period) config(): Option<T::Moment>;
-        QuorumHavlingPeriod get(quorum_havling_period) config(): Option<T::Moment>;
-        ExecuteProposalPeriod get(execute_proposal_period) config(): Option<T::Moment>;
-        MaxDepositDivisor get(max_deposit_divisor) config(): Option<u64>;
-        // DAO parameter end
+        VoteNo get(vote_no): map (u64, T::AccountId) => bool;
+        VoteYes get(vote_yes): map (u64, T::AccountId) => bool;
 
-        Proposals get(proposals): map u64 => Proposal<T::TokenBalance, T::AccountId, T::Moment, T::Hash>;
+        // Proposal ids are shared by all the DAOs
+        Proposals get(proposals): map u64 => Proposal<BalanceOf<T>, T::AccountId, T::Moment, T::Hash>;
         ProposalCount get(proposal_count): u64;
+        ProposalDao get(dao_of): map u64 => u64;
+        // The proposals of a DAO by their index in it, up to the `proposal_count` of the DAO
+        DaoProposals get(dao_proposal): map (u64, u64) => u64;
+        // The runtime call dispatched when a call proposal passes
+        ProposalCalls get(proposal_call): map u64 => Option<T::Proposal>;
+        // Proposals paying native currency from the treasury instead of tokens
+        NativePayouts get(pays_native): map u64 => bool;
+        // Kept to execute the proposal automatically, it must match the proposal hash
+        TransactionData get(transaction_data): map u64 => Vec<u8>;
+        // Tasks run in `on_finalize`, by the clock of their DAO and the time they are due
+        // at. The times of a clock are checked in order from its cursor.
+        ScheduledTasks get(scheduled_tasks): map (Clock, T::Moment) => Vec<ScheduledTask>;
+        ScheduleCursors get(schedule_cursor): map Clock => Option<T::Moment>;
 
-        AllowedRecipients get(allowed_recipients): map T::AccountId => bool;
+        AllowedRecipients get(allowed_recipients): map (u64, T::AccountId) => bool;
         // Map of addresses blocked during a vote (not allowed to transfer DAO
         // tokens). The address points to the proposal ID.
-        Blocked get(blocked): map T::AccountId => u64;
+        Blocked get(blocked): map (u64, T::AccountId) => u64;
         // Map of addresses and proposal voted on by this address
         VotingRegister get(voting_register): map (T::AccountId, u64) => u64;
         VotingRegisterCount get(voting_register_count): map T::AccountId => u64;
-        SumOfProposalDeposits get(sum_of_proposal_deposits): T::TokenBalance;
+        // Weight counted in the tally and conviction of a vote
+        VoteWeights get(vote_weight): map (u64, T::AccountId) => BalanceOf<T>;
+        VoteConvictions get(vote_conviction): map (u64, T::AccountId) => u8;
+        // Tokens backing a vote before they are weighted, the credits spent for a quadratic vote
+        VoteCredits get(vote_credits): map (u64, T::AccountId) => BalanceOf<T>;
+        VotingModes get(voting_mode): map u64 => VotingMode;
+        // (dao_id, voter) => quadratic proposals voted on before their deadline and the credits spent
+        QuadraticCommitments get(quadratic_commitments): map (u64, T::AccountId) => Vec<(u64, BalanceOf<T>)>;
+        // Start of the reveal window of a secret proposal, it ends at the voting deadline
+        RevealStarts get(reveal_start): map u64 => Option<T::Moment>;
+        // (proposal_id, voter) => hash of (proposal_id, supports, salt) and the deposit locked
+        VoteCommits get(vote_commit): map (u64, T::AccountId) => Option<(T::Hash, BalanceOf<T>)>;
+        Committers get(committers): map u64 => Vec<T::AccountId>;
+        // Sum of the tokens backing the (yes, no) votes of a proposal
+        RawTally get(raw_tally): map u64 => (BalanceOf<T>, BalanceOf<T>);
+        // Optional topic of a proposal, a topic delegation overrides the global one
+        ProposalTopics get(proposal_topic): map u64 => Option<u32>;
+        // (dao_id, delegator, topic) => delegate, `None` is the global delegation
+        Delegations get(delegate_of): map (u64, T::AccountId, Option<u32>) => Option<T::AccountId>;
+        Delegators get(delegators_of): map (u64, T::AccountId, Option<u32>) => Vec<T::AccountId>;
+        // (proposal_id, delegator) => voter who counted the delegator's tokens and the amount
+        DelegatedVotes get(delegated_vote): map (u64, T::AccountId) => Option<(T::AccountId, BalanceOf<T>)>;
+        CountedDelegators get(counted_delegators): map (u64, T::AccountId) => Vec<T::AccountId>;
+        // (dao_id, delegator) => proposals which counted the delegator's tokens, the closed
+        // ones are pruned when another one is added
+        DelegatedProposals get(delegated_proposals): map (u64, T::AccountId) => Vec<u64>;
+        // Tokens locked by conviction votes and the time they can be unlocked
+        ConvictionLocks get(conviction_lock): map (u64, T::AccountId) => Option<(BalanceOf<T>, T::Moment)>;
+
+        // (dao_id, action hash) => council members who approved the action
+        CuratorApprovals get(curator_approvals): map (u64, T::Hash) => Vec<T::AccountId>;
+        PendingCuratorActions get(pending_curator_actions): map u64 => Vec<T::Hash>;
+        // The council and threshold set by a council proposal if it passes
+        CouncilProposals get(council_proposal): map u64 => Option<(Vec<T::AccountId>, u32)>;
+        // The parameter set by a parameter proposal and the time it takes effect once passed
+        ParameterProposals get(parameter_proposal): map u64 => Option<DaoParameterOf<T>>;
+        ParameterChangeTimes get(parameter_change_at): map u64 => Option<T::Moment>;
+        // End of the challenge window of an optimistic proposal which was not vetoed
+        ChallengeEnds get(challenge_end): map u64 => Option<T::Moment>;
+        // Tokens vetoing an optimistic proposal and the accounts which vetoed it
+        VetoWeights get(veto_weight): map u64 => BalanceOf<T>;
+        Vetoes get(vetoed): map (u64, T::AccountId) => bool;
+        // (tokens, native currency) of the reward pool reserved for voters and not claimed yet
+        RewardsReserved get(rewards_reserved): map u64 => (BalanceOf<T>, BalanceOf<T>);
+        // Rewards of the voters of a closed proposal: tokens, native currency, total vote
+        // weight and the end of the claim period
+        ProposalRewards get(proposal_reward): map u64 => Option<(BalanceOf<T>, BalanceOf<T>, BalanceOf<T>, T::Moment)>;
+        UnclaimedRewards get(unclaimed_rewards): map u64 => (BalanceOf<T>, BalanceOf<T>);
+        RewardClaimed get(reward_claimed): map (u64, T::AccountId) => bool;
+        // Bounties are funded by a native payout of their proposal to the bounty account
+        Bounties get(bounties): map u64 => Option<BountyOf<T>>;
+        BountyCount get(bounty_count): u64;
+        BountyProposals get(bounty_of_proposal): map u64 => Option<u64>;
+        // Passed proposals waiting for the end of their grace period to be executed
+        GracePeriodEnds get(grace_period_end): map u64 => Option<T::Moment>;
+
+        // Child DAOs created by `split_dao`, keyed by the `new_curator` proposal
+        ChildDaos get(child_daos): map u64 => Option<ChildDao<BalanceOf<T>>>;
     }
 
     // initialize the DAO
@@ -74,11 +419,58 @@ decl_storage! {
     // make sender an admin if it's the curator account set in genesis config
     // curator then has all the tokens and admin rights to the DAO
     add_extra_genesis {
+        // stores the curator in the genesis config
+        config(curator): T::AccountId;
+        // DAO parameter begin
+        config(min_proposal_deposit): BalanceOf<T>;
+        config(min_quorum_divisor): u64;
+        config(min_proposal_debate_period): T::Moment;
+        config(max_proposal_debate_period): T::Moment;
+        config(quorum_havling_period): T::Moment;
+        config(execute_proposal_period): T::Moment;
+        config(max_deposit_divisor): u64;
+        config(deposit_slash_percent): u32;
+        config(vote_lock_period): T::Moment;
+        config(grace_period): T::Moment;
+        config(parameter_timelock): T::Moment;
+        config(challenge_period): T::Moment;
+        config(veto_divisor): u64;
+        config(reward_divisor): u64;
+        config(reward_claim_period): T::Moment;
+        // the periods above are block numbers with `Clock::BlockNumber`
+        config(clock): Clock;
+        // DAO parameter end
         build(|storage: &mut runtime_primitives::StorageOverlay, _: &mut runtime_primitives::ChildrenStorageOverlay, config: &GenesisConfig<T>| {
             with_storage(storage, || {
-                <LastTimeMinQuorumMet<T>>::put(<timestamp::Module<T>>::get());
+                <Daos<T>>::insert(0, DaoInfo {
+                    curator: config.curator.clone(),
+                    council: vec![config.curator.clone()],
+                    threshold: 1,
+                    token: None,
+                    membership: None,
+                    clock: config.clock,
+                    min_proposal_deposit: config.min_proposal_deposit,
+                    min_quorum_divisor: config.min_quorum_divisor,
+                    min_proposal_debate_period: config.min_proposal_debate_period.clone(),
+                    max_proposal_debate_period: config.max_proposal_debate_period.clone(),
+                    quorum_havling_period: config.quorum_havling_period.clone(),
+                    execute_proposal_period: config.execute_proposal_period.clone(),
+                    max_deposit_divisor: config.max_deposit_divisor,
+                    deposit_slash_percent: config.deposit_slash_percent,
+                    vote_lock_period: config.vote_lock_period.clone(),
+                    grace_period: config.grace_period.clone(),
+                    parameter_timelock: config.parameter_timelock.clone(),
+                    challenge_period: config.challenge_period.clone(),
+                    veto_divisor: config.veto_divisor,
+                    reward_divisor: config.reward_divisor,
+                    reward_claim_period: config.reward_claim_period.clone(),
+                    last_time_min_quorum_met: config.clock.now::<T>(),
+                    sum_of_proposal_deposits: Default::default(),
+                    proposal_count: 0,
+                });
+                <DaoCount<T>>::put(1);
                 <ProposalCount<T>>::put(1);
-                <AllowedRecipients<T>>::insert(config.curator.clone(), true);
+                <AllowedRecipients<T>>::insert((0, config.curator.clone()), true);
                 //<Module<T>>::init().unwrap();
             });
         })
@@ -90,492 +482,3523 @@ decl_event!(
     pub enum Event<T>
     where
         AccountId = <T as system::Trait>::AccountId,
+        Hash = <T as system::Trait>::Hash,
         Balance = <T as token::Trait>::TokenBalance,
+        Moment = <T as timestamp::Trait>::Moment,
     {
+        // dao_id, curator, lockabletoken id
+        DaoCreated(u64, AccountId, u64),
         ProposalAdded(u64, AccountId, Balance, Vec<u8>),
-        ProposalTaillied(u64, bool, Balance),
+        // proposal_id, result, quorum, yes and no weight, raw yes and no tokens
+        ProposalTaillied(u64, bool, Balance, Balance, Balance, Balance, Balance),
         // when a proposal is voted on
         Voted(u64, bool, AccountId),
-        AllowedRecipientChanged(AccountId, bool),
+        // dao_id, recipient, allowed
+        AllowedRecipientChanged(u64, AccountId, bool),
+        // dao_id, recipient, amount, paid in native currency
+        TreasurySpent(u64, AccountId, Balance, bool),
+        // proposal_id, member, child dao_id, tokens moved, funds moved, treasury funds moved
+        DaoSplit(u64, AccountId, u64, Balance, Balance, Balance),
+        // proposal_id, whether the dispatched call succeeded
+        ProposalExecuted(u64, bool),
+        // proposal_id, the payout failed and the proposal waits to be enacted again
+        EnactmentFailed(u64),
+        // proposal_id, conviction, vote weight
+        VotedWithConviction(u64, u8, Balance),
+        // proposal_id, credits spent, vote weight
+        VotedQuadratic(u64, Balance, Balance),
+        // dao_id, delegator, delegate, topic
+        Delegated(u64, AccountId, AccountId, Option<u32>),
+        Undelegated(u64, AccountId, Option<u32>),
+        Unlocked(u64, AccountId, Balance),
+        // dao_id, council member, action hash, approvals so far
+        CuratorActionApproved(u64, AccountId, Hash, u32),
+        CuratorActionExecuted(u64, Hash),
+        // dao_id, council, threshold
+        CouncilChanged(u64, Vec<AccountId>, u32),
+        // dao_id, proposal_id, time the parameter change takes effect
+        ParameterChangeScheduled(u64, u64, Moment),
+        // dao_id, proposal_id
+        ParameterChanged(u64, u64),
+        // dao_id, proposal_id, the parameter is out of its bounds when it would take effect
+        ParameterChangeFailed(u64, u64),
+        // proposal_id, voter
+        VoteCommitted(u64, AccountId),
+        // proposal_id, vetoing account, vetoed tokens
+        Vetoed(u64, AccountId, Balance),
+        // proposal_id, the optimistic proposal is now a normal vote
+        VetoSucceeded(u64),
+        // proposal_id, voter, deposit sent to the treasury because the vote was not revealed
+        CommitForfeited(u64, AccountId, Balance),
+        // bounty_id, proposal_id
+        BountyProposed(u64, u64),
+        BountyFunded(u64),
+        // bounty_id, contributor
+        BountyAssigned(u64, AccountId),
+        BountySubmitted(u64, AccountId),
+        // bounty_id, contributor, time the payout unlocks
+        BountyAccepted(u64, AccountId, Moment),
+        // bounty_id, contributor, budget paid
+        BountyPaid(u64, AccountId, Balance),
+        // bounty_id, budget returned to the treasury
+        BountyCancelled(u64, Balance),
+        // bounty_id, new curator
+        BountyCuratorChanged(u64, AccountId),
+        // dao_id, funder, amount, paid in native currency
+        RewardsFunded(u64, AccountId, Balance, bool),
+        // proposal_id, tokens and native currency reserved for its voters
+        RewardsReserved(u64, Balance, Balance),
+        // proposal_id, voter, tokens and native currency claimed
+        RewardClaimed(u64, AccountId, Balance, Balance),
+        // proposal_id, creator, part of the deposit sent to the treasury
+        DepositSlashed(u64, AccountId, Balance),
+        // proposal_id, member, tokens burned, funds withdrawn, treasury funds withdrawn
+        Ragequit(u64, AccountId, Balance, Balance, Balance),
     }
 );
 
 decl_module! {
-  pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+  pub struct Module<T: Trait> for enum Call where origin: <T as system::Trait>::Origin {
     // initialize events for this module
     fn deposit_event<T>() = default;
 
+    // execute the proposals that reached their deadline, a bounded number per block
+    fn on_finalize(_n: T::BlockNumber) {
+        let mut due = Vec::new();
+        Self::take_due(Clock::Timestamp, &mut due);
+        Self::take_due(Clock::BlockNumber, &mut due);
+
+        for task in due {
+            match task {
+                ScheduledTask::Proposal(proposal_id) => Self::execute_scheduled(proposal_id),
+                ScheduledTask::ExpireRewards(proposal_id) => Self::expire_rewards(proposal_id),
+            }
+        }
+    }
+
     fn init(origin) -> Result {
-        let curator = Self::curator().ok_or("curator not set?")?;
+        let curator = Self::dao_info(0)?.curator;
         let sender = ensure_signed(origin)?;
         ensure!(sender == curator, "Only the curator set in genesis config can initialize");
         <token::Module<T>>::init(curator)?;
         Ok(())
     }
 
+    /// Create a DAO governed by the `lockabletoken` asset `token_id`, the sender becomes
+    /// its curator. The tokens sent to its `treasury_account` are the funds of the DAO.
+    /// With a `membership`, the DAO is NFT gated: the
+    /// votes are weighted by the `erc721` tokens of the voters. The periods are measured
+    /// with `clock`.
+    fn create_dao(
+        origin,
+        token_id: u64,
+        min_proposal_deposit: BalanceOf<T>,
+        min_quorum_divisor: u64,
+        min_proposal_debate_period: T::Moment,
+        max_proposal_debate_period: T::Moment,
+        quorum_havling_period: T::Moment,
+        execute_proposal_period: T::Moment,
+        max_deposit_divisor: u64,
+        deposit_slash_percent: u32,
+        vote_lock_period: T::Moment,
+        grace_period: T::Moment,
+        parameter_timelock: T::Moment,
+        challenge_period: T::Moment,
+        veto_divisor: u64,
+        reward_divisor: u64,
+        reward_claim_period: T::Moment,
+        membership: Option<NftMembershipOf<T>>,
+        clock: Clock
+    ) -> Result {
+        let sender = ensure_signed(origin)?;
+        ensure!(<lockabletoken::Module<T>>::total_supply(token_id) > <BalanceOf<T>>::sa(0), "The token does not exist");
+        ensure!(min_quorum_divisor > 0 && max_deposit_divisor > 0 && veto_divisor > 0 && reward_divisor > 0,
+            "The divisors can not be zero");
+        ensure!(deposit_slash_percent <= 100, "The slash percentage can not be more than 100");
+        ensure!(max_proposal_debate_period > min_proposal_debate_period, "Invalid debate periods");
+
+        let dao_id = Self::dao_count();
+        let mut council = Vec::new();
+        council.push(sender.clone());
+        <Daos<T>>::insert(dao_id, DaoInfo {
+            curator: sender.clone(),
+            council,
+            threshold: 1,
+            token: Some(token_id),
+            membership,
+            clock,
+            min_proposal_deposit,
+            min_quorum_divisor,
+            min_proposal_debate_period,
+            max_proposal_debate_period,
+            quorum_havling_period,
+            execute_proposal_period,
+            max_deposit_divisor,
+            deposit_slash_percent,
+            vote_lock_period,
+            grace_period,
+            parameter_timelock,
+            challenge_period,
+            veto_divisor,
+            reward_divisor,
+            reward_claim_period,
+            last_time_min_quorum_met: clock.now::<T>(),
+            sum_of_proposal_deposits: <BalanceOf<T>>::sa(0),
+            proposal_count: 0,
+        });
+        <DaoCount<T>>::put(dao_id + 1);
+        <AllowedRecipients<T>>::insert((dao_id, sender.clone()), true);
+
+        Self::deposit_event(RawEvent::DaoCreated(dao_id, sender, token_id));
+
+        Ok(())
+    }
+
     fn new_proposal(
         origin,
+        dao_id: u64,
         recipient: T::AccountId,
-        amount: T::TokenBalance,
+        amount: BalanceOf<T>,
         description: Vec<u8>,
         transaction_data: Vec<u8>,
         debating_period: T::Moment,
-        deposit: T::TokenBalance
+        deposit: BalanceOf<T>
     ) -> Result{
         let sender = ensure_signed(origin)?;
-        ensure!(<token::Module<T>>::balance_of(sender.clone()) > T::TokenBalance::sa(0), "This account doesn't hold the token");
-
-        ensure!(Self::allowed_recipients(recipient.clone()), "The recipient is not in whitelist");
+        ensure!(Self::balance_of(dao_id, sender.clone()) > <BalanceOf<T>>::sa(0), "This account doesn't hold the token");
 
-        let min_proposal_debate_period  = Self::min_proposal_debate_period().ok_or("MinProposalDebatePeriod not set.")?;
-        ensure!(debating_period > min_proposal_debate_period, "debating_period too short");
-        ensure!(debating_period < T::Moment::sa(8*7*24*3600), "debating_period too long");
+        ensure!(Self::allowed_recipients((dao_id, recipient.clone())), "The recipient is not in whitelist");
 
-        let min_deposit = Self::min_proposal_deposit().ok_or("MinProposalDeposit not set?")?;
-        ensure!(deposit > min_deposit, "deposit should be more than min_deposit");
-
-        let count = Self::proposal_count();
-        // to prevent curator from halving quorum before first proposal
-        if count ==1 {
-            <LastTimeMinQuorumMet<T>>::put(<timestamp::Module<T>>::get());
-        }
+        Self::create_proposal(dao_id, sender, recipient, amount, description, transaction_data, debating_period, deposit, false)
+    }
 
-        // safe?
-        let proposal_id = count;
-        <ProposalCount<T>>::put(count + 1);
+    /// Same as `new_proposal`, but the proposal is voted on with `vote_quadratic`.
+    fn new_quadratic_proposal(
+        origin,
+        dao_id: u64,
+        recipient: T::AccountId,
+        amount: BalanceOf<T>,
+        description: Vec<u8>,
+        transaction_data: Vec<u8>,
+        debating_period: T::Moment,
+        deposit: BalanceOf<T>
+    ) -> Result{
+        let sender = ensure_signed(origin)?;
+        ensure!(Self::membership_of(dao_id).is_none(), "An NFT gated DAO only votes with its NFTs");
+        ensure!(Self::balance_of(dao_id, sender.clone()) > <BalanceOf<T>>::sa(0), "This account doesn't hold the token");
 
-        let voting_deadline = <timestamp::Module<T>>::get().checked_add(&debating_period).ok_or("Overflow when setting voting deadline.")?;
+        ensure!(Self::allowed_recipients((dao_id, recipient.clone())), "The recipient is not in whitelist");
 
-        let mut buf = Vec::new();
-        buf.extend_from_slice(&amount.encode());
-        buf.extend_from_slice(&proposal_id.encode());
-        buf.extend_from_slice(&recipient.encode());
-        buf.extend_from_slice(&transaction_data);
-        let proposal_hash = <T as system::Trait>::Hashing::hash(&buf[..]);
+        let proposal_id = Self::proposal_count();
+        Self::create_proposal(dao_id, sender, recipient, amount, description, transaction_data, debating_period, deposit, false)?;
+        <VotingModes<T>>::insert(proposal_id, VotingMode::Quadratic);
 
-        let p = Proposal{
-            recipient: recipient.clone(),
-            amount,
-            description: description.clone(),
-            voting_deadline,
-            open: true,
-            proposal_passed: false,
-            proposal_hash,
-            proposal_deposit: deposit,
-            new_curator: false,
-            yea: T::TokenBalance::sa(0),
-            nay: T::TokenBalance::sa(0),
-            creator: sender.clone(),
-        };
+        Ok(())
+    }
 
-        let sum = Self::sum_of_proposal_deposits();
-        let new_sum = sum.checked_add(&deposit).ok_or("Overflow in calculating sumOfProposalDeposits.")?;
-        <SumOfProposalDeposits<T>>::put(new_sum);
+    /// Same as `new_proposal`, but the votes are secret. They are committed with
+    /// `commit_vote` during `debating_period` and revealed with `reveal_vote` during the
+    /// following `reveal_period`.
+    fn new_secret_proposal(
+        origin,
+        dao_id: u64,
+        recipient: T::AccountId,
+        amount: BalanceOf<T>,
+        description: Vec<u8>,
+        transaction_data: Vec<u8>,
+        debating_period: T::Moment,
+        reveal_period: T::Moment,
+        deposit: BalanceOf<T>
+    ) -> Result{
+        let sender = ensure_signed(origin)?;
+        ensure!(Self::membership_of(dao_id).is_none(), "An NFT gated DAO only votes with its NFTs");
+        ensure!(Self::balance_of(dao_id, sender.clone()) > <BalanceOf<T>>::sa(0), "This account doesn't hold the token");
+        ensure!(reveal_period > T::Moment::sa(0), "The reveal period can not be zero");
 
-        <Proposals<T>>::insert(proposal_id, p);
-        <token::Module<T>>::lock(sender, deposit, proposal_id)?;
+        ensure!(Self::allowed_recipients((dao_id, recipient.clone())), "The recipient is not in whitelist");
 
-        Self::deposit_event(RawEvent::ProposalAdded(
-            proposal_id,
-            recipient,
-            amount,
-            description
-        ));
+        let proposal_id = Self::proposal_count();
+        let total_period = debating_period.checked_add(&reveal_period).ok_or("Overflow when setting voting deadline.")?;
+        Self::create_proposal(dao_id, sender, recipient, amount, description, transaction_data, total_period, deposit, false)?;
+        <VotingModes<T>>::insert(proposal_id, VotingMode::Secret);
+        <RevealStarts<T>>::insert(proposal_id, Self::proposals(proposal_id).voting_deadline - reveal_period);
 
         Ok(())
     }
 
-    fn vote(origin, proposal_id: u64, supports_proposal: bool) -> Result{
+    /// Same as `new_proposal`, but the proposal passes without a vote at the end of the
+    /// `challenge_period` of the DAO, unless it is vetoed. A successful veto turns it
+    /// into a normal vote ending after `debating_period`.
+    fn new_optimistic_proposal(
+        origin,
+        dao_id: u64,
+        recipient: T::AccountId,
+        amount: BalanceOf<T>,
+        description: Vec<u8>,
+        transaction_data: Vec<u8>,
+        debating_period: T::Moment,
+        deposit: BalanceOf<T>
+    ) -> Result{
         let sender = ensure_signed(origin)?;
-        ensure!(Self::blocked(sender.clone()) == 0
-               || Self::proposals(proposal_id).voting_deadline > Self::proposals(Self::blocked(sender.clone())).voting_deadline,
-               "This is blocked");
-        Self::_unvote(sender.clone(), proposal_id)?;
-
-        <Proposals<T>>::mutate(proposal_id, |p| {
-            <Blocked<T>>::insert(sender.clone(), proposal_id);
-            if supports_proposal {
-                p.yea += <token::Module<T>>::balance_of(sender.clone());
-                <VoteYes<T>>::insert(sender.clone(), true);
-            } else {
-                p.nay += <token::Module<T>>::balance_of(sender.clone());
-                <VoteNo<T>>::insert(sender.clone(), true);
-            }
-        });
+        ensure!(Self::balance_of(dao_id, sender.clone()) > <BalanceOf<T>>::sa(0), "This account doesn't hold the token");
+        let challenge_period = Self::dao_info(dao_id)?.challenge_period;
+        ensure!(challenge_period < debating_period, "debating_period too short");
 
-        let voting_register_count = Self::voting_register_count(sender.clone());
-        <VotingRegister<T>>::insert((sender.clone(), voting_register_count), proposal_id);
-        <VotingRegisterCount<T>>::insert(sender.clone(), voting_register_count + 1);
+        ensure!(Self::allowed_recipients((dao_id, recipient.clone())), "The recipient is not in whitelist");
 
-        Self::deposit_event(RawEvent::Voted(proposal_id, supports_proposal, sender));
+        let proposal_id = Self::proposal_count();
+        Self::create_proposal(dao_id, sender, recipient, amount, description, transaction_data, debating_period, deposit, false)?;
+        let end = Self::now(dao_id) + challenge_period;
+        <ChallengeEnds<T>>::insert(proposal_id, end.clone());
+        Self::schedule(proposal_id, end);
 
         Ok(())
     }
 
-    fn execute_proposal(proposal_id: u64, transaction_data: Vec<u8>) -> Result{
-        let execute_proposal_period = Self::execute_proposal_period().ok_or("execute_proposal_period not set?")?;
-        let p = Self::proposals(proposal_id);
-        let now = <timestamp::Module<T>>::get();
+    /// Veto an optimistic proposal during its challenge window with the sender's token
+    /// balance, which is locked until the voting deadline.
+    fn veto(origin, proposal_id: u64) -> Result {
+        let sender = ensure_signed(origin)?;
+        let end = Self::challenge_end(proposal_id).ok_or("The proposal can not be vetoed")?;
+        ensure!(Self::now_for(proposal_id) < end, "The challenge period has ended");
+        ensure!(!Self::vetoed((proposal_id, sender.clone())), "You already vetoed this proposal");
+        let dao_id = Self::dao_of(proposal_id);
+        let dao = Self::dao_info(dao_id)?;
+        let balance = Self::voting_balance(dao_id, sender.clone());
+        ensure!(balance > <BalanceOf<T>>::sa(0), "This account doesn't hold the token");
 
-        if p.open && now > p.voting_deadline.clone() + execute_proposal_period {
-            Self::close_proposal(proposal_id)?;
-            ensure!(false,"The execution deadline has passed.");
-        }
+        <Vetoes<T>>::insert((proposal_id, sender.clone()), true);
+        let weight = Self::veto_weight(proposal_id) + balance;
+        <VetoWeights<T>>::insert(proposal_id, weight);
+        Self::extend_conviction_lock(dao_id, sender.clone(), balance, Self::proposals(proposal_id).voting_deadline);
+        Self::deposit_event(RawEvent::Vetoed(proposal_id, sender, balance));
 
-        ensure!(now >= p.voting_deadline, "It has not yet reached the voting deadline.");
-        ensure!(p.open, "Proposal not open");
-        ensure!(!p.proposal_passed, "Proposal has already been passed");
+        if weight >= Self::voting_supply(dao_id) / <BalanceOf<T>>::sa(dao.veto_divisor) {
+            // the proposal is now decided by the vote scheduled at its voting deadline
+            <ChallengeEnds<T>>::remove(proposal_id);
+            Self::deposit_event(RawEvent::VetoSucceeded(proposal_id));
+        }
 
-        let mut buf = Vec::new();
-        buf.extend_from_slice(&p.amount.encode());
-        buf.extend_from_slice(&proposal_id.encode());
-        buf.extend_from_slice(&p.recipient.encode());
-        buf.extend_from_slice(&transaction_data);
-        let proposal_hash = <T as system::Trait>::Hashing::hash(&buf[..]);
+        Ok(())
+    }
 
-        ensure!(p.proposal_hash == proposal_hash, "Not match the proposal hash");
+    /// Same as `new_proposal`, but `amount` is paid in native currency from the DAO treasury.
+    fn new_native_proposal(
+        origin,
+        dao_id: u64,
+        recipient: T::AccountId,
+        amount: BalanceOf<T>,
+        description: Vec<u8>,
+        transaction_data: Vec<u8>,
+        debating_period: T::Moment,
+        deposit: BalanceOf<T>
+    ) -> Result{
+        let sender = ensure_signed(origin)?;
+        ensure!(Self::balance_of(dao_id, sender.clone()) > <BalanceOf<T>>::sa(0), "This account doesn't hold the token");
 
-        if !Self::allowed_recipients(p.recipient.clone()) {
-            Self::close_proposal(proposal_id)?;
-            <token::Module<T>>::unlock(p.creator.clone(), p.proposal_deposit, proposal_id)?;
-            return Err("No such recipient in the whitelist.");
-        }
+        ensure!(Self::allowed_recipients((dao_id, recipient.clone())), "The recipient is not in whitelist");
 
-        let mut proposal_check = true;
-        let actual_balance = Self::actual_balance();
-        if p.amount > actual_balance {
-            proposal_check = false;
-        }
-        let quorum = p.yea;
+        let proposal_id = Self::proposal_count();
+        Self::create_proposal(dao_id, sender, recipient, amount, description, transaction_data, debating_period, deposit, false)?;
+        <NativePayouts<T>>::insert(proposal_id, true);
 
-        // Need improved
-        let min_quorum_divisor = Self::min_quorum_divisor().ok_or("min_quorum_divisor not set?")?;
-        if transaction_data.len() >= 4 && quorum < Self::min_quorum(min_quorum_divisor, actual_balance)
-        {
-            proposal_check = false;
-        }
+        Ok(())
+    }
 
-        if quorum >= Self::min_quorum(min_quorum_divisor, p.amount) {
-            <token::Module<T>>::unlock(p.creator.clone(), p.proposal_deposit, proposal_id)?;
-            <LastTimeMinQuorumMet<T>>::put(now);
-            if quorum > <token::Module<T>>::total_supply() / T::TokenBalance::sa(7) {
-                <MinQuorumDivisor<T>>::put(7);
-            }
-        }
+    /// Propose `new_curator` as the curator of a child DAO. The members voting yes can
+    /// move to it with their share of the DAO funds by calling `split_dao` after the deadline.
+    fn new_curator_proposal(
+        origin,
+        dao_id: u64,
+        new_curator: T::AccountId,
+        description: Vec<u8>,
+        debating_period: T::Moment,
+        deposit: BalanceOf<T>
+    ) -> Result {
+        let sender = ensure_signed(origin)?;
+        ensure!(Self::balance_of(dao_id, sender.clone()) > <BalanceOf<T>>::sa(0), "This account doesn't hold the token");
 
-        <Proposals<T>>::mutate(proposal_id, |p|{
-            if quorum >= Self::min_quorum(min_quorum_divisor, p.amount) && p.yea > p.nay && proposal_check {
-                p.proposal_passed = true;
-            }
-        });
-        Self::close_proposal(proposal_id)?;
+        Self::create_proposal(dao_id, sender, new_curator, <BalanceOf<T>>::sa(0), description, Vec::new(), debating_period, deposit, true)
+    }
 
-        <token::Module<T>>::lock(Self::curator().unwrap(), p.amount, proposal_id)?;
-        <token::Module<T>>::unlock(p.recipient.clone(), p.amount, proposal_id)?;
+    /// Propose a runtime call that is dispatched with the DAO origin if the proposal
+    /// passes. It must be executed with the encoded call as `transaction_data`.
+    fn new_call_proposal(
+        origin,
+        dao_id: u64,
+        call: Box<<T as Trait>::Proposal>,
+        description: Vec<u8>,
+        debating_period: T::Moment,
+        deposit: BalanceOf<T>
+    ) -> Result {
+        let sender = ensure_signed(origin)?;
+        ensure!(Self::balance_of(dao_id, sender.clone()) > <BalanceOf<T>>::sa(0), "This account doesn't hold the token");
 
-        Self::deposit_event(RawEvent::ProposalTaillied(proposal_id, true, quorum));
+        let proposal_id = Self::proposal_count();
+        Self::create_proposal(dao_id, sender.clone(), sender, <BalanceOf<T>>::sa(0), description, call.encode(), debating_period, deposit, false)?;
+        <ProposalCalls<T>>::insert(proposal_id, *call);
 
         Ok(())
     }
 
-    fn change_min_proposal_deposit(origin, new_min_proposal_deposit: T::TokenBalance) -> Result{
+    /// Propose to replace the curator council of a DAO, `threshold` of the `council`
+    /// members approve the curator actions if the proposal passes.
+    fn new_council_proposal(
+        origin,
+        dao_id: u64,
+        council: Vec<T::AccountId>,
+        threshold: u32,
+        description: Vec<u8>,
+        debating_period: T::Moment,
+        deposit: BalanceOf<T>
+    ) -> Result {
         let sender = ensure_signed(origin)?;
-        if sender != Self::curator().unwrap() {
-            return Err("Only curator can change min_proposal_deposit");
-        }
-        let max_deposit_divisor = Self::max_deposit_divisor().ok_or("max_deposit_divisor not set?")?;
-        if sender != Self::curator().unwrap() || new_min_proposal_deposit > Self::actual_balance() / T::TokenBalance::sa(max_deposit_divisor.into())
-        {
-            return Err("change_proposal_deposit failed");
+        ensure!(Self::balance_of(dao_id, sender.clone()) > <BalanceOf<T>>::sa(0), "This account doesn't hold the token");
+        ensure!(!council.is_empty() && council.len() <= MAX_COUNCIL_SIZE, "Invalid council size");
+        ensure!(threshold > 0 && threshold as usize <= council.len(), "Invalid threshold");
+        for (i, member) in council.iter().enumerate() {
+            ensure!(!council[i + 1..].contains(member), "Duplicate council member");
         }
-        <MinProposalDeposit<T>>::put(new_min_proposal_deposit);
+
+        let proposal_id = Self::proposal_count();
+        let transaction_data = (council.clone(), threshold).encode();
+        Self::create_proposal(dao_id, sender.clone(), sender, <BalanceOf<T>>::sa(0), description, transaction_data, debating_period, deposit, false)?;
+        <CouncilProposals<T>>::insert(proposal_id, (council, threshold));
+
         Ok(())
     }
 
-    fn change_allowed_recipients(origin, recipient: T::AccountId, allowed: bool) ->Result{
+    /// Propose to change a parameter of the DAO. The change takes effect
+    /// `parameter_timelock` after the proposal is executed.
+    fn new_parameter_proposal(
+        origin,
+        dao_id: u64,
+        parameter: DaoParameterOf<T>,
+        description: Vec<u8>,
+        debating_period: T::Moment,
+        deposit: BalanceOf<T>
+    ) -> Result {
         let sender = ensure_signed(origin)?;
-        if sender != Self::curator().unwrap() {
-            return Err("Only curator can change whitelist");
-        }
-        <AllowedRecipients<T>>::insert(recipient.clone(), allowed);
-        Self::deposit_event(RawEvent::AllowedRecipientChanged(recipient, allowed));
+        ensure!(Self::balance_of(dao_id, sender.clone()) > <BalanceOf<T>>::sa(0), "This account doesn't hold the token");
+        Self::check_parameter(dao_id, &parameter)?;
+
+        let proposal_id = Self::proposal_count();
+        let transaction_data = parameter.encode();
+        Self::create_proposal(dao_id, sender.clone(), sender, <BalanceOf<T>>::sa(0), description, transaction_data, debating_period, deposit, false)?;
+        <ParameterProposals<T>>::insert(proposal_id, parameter);
+
         Ok(())
     }
 
-    fn halvemin_quorum(origin) -> Result {
+    /// Vote with the sender's token balance. See `MAX_CONVICTION` for how the conviction
+    /// scales the vote weight and locks the tokens.
+    /// In an NFT gated DAO the sender votes with its NFTs instead, without conviction, and
+    /// they can not be transferred until the voting deadline.
+    fn vote(origin, proposal_id: u64, supports_proposal: bool, conviction: u8) -> Result{
         let sender = ensure_signed(origin)?;
-        let now = <timestamp::Module<T>>::get();
-        let quorum_havling_period = Self::quorum_havling_period().ok_or("quorum_havling_period not set ?")?;
-        let min_proposal_debate_period = Self::min_proposal_debate_period().ok_or("min_proposal_debate_period not set?")?;
-        let last_time_min_quorum_met = Self::last_time_min_quorum_met().ok_or("last_time_min_quorum_met not set?")?;
-        let min_quorum_divisor = Self::min_quorum_divisor().ok_or("minQuorumDivisor not set?")?;
+        ensure!(conviction <= MAX_CONVICTION, "conviction too high");
+        ensure!(Self::voting_mode(proposal_id) != VotingMode::Secret, "This proposal is voted with commit_vote");
+        ensure!(Self::voting_mode(proposal_id) == VotingMode::Token, "This proposal is voted with vote_quadratic");
+        let dao_id = Self::dao_of(proposal_id);
 
-        if (last_time_min_quorum_met < (now.clone() - quorum_havling_period) || sender == Self::curator().unwrap())
-            && last_time_min_quorum_met < (now.clone() - min_proposal_debate_period)
-            && Self::proposal_count() > 1 {
-            <LastTimeMinQuorumMet<T>>::put(now);
-            <MinQuorumDivisor<T>>::put(min_quorum_divisor * 2u64);
+        if Self::membership_of(dao_id).is_some() {
+            ensure!(conviction == 0, "NFT votes have no conviction");
+            let weight = Self::voting_balance(dao_id, sender.clone());
+            ensure!(weight > <BalanceOf<T>>::sa(0), "This account doesn't hold a membership NFT");
+            // NFT votes are personal, delegations do not apply
+            Self::record_vote(sender.clone(), proposal_id, supports_proposal, weight, weight, false)?;
+            Self::extend_conviction_lock(dao_id, sender, weight, Self::proposals(proposal_id).voting_deadline);
             return Ok(());
+        }
+
+        let balance = Self::balance_of(dao_id, sender.clone());
+        let weight = if conviction == 0 {
+            balance / <BalanceOf<T>>::sa(10)
         } else {
-            return Err("halvemin_quorum failed.");
+            balance * <BalanceOf<T>>::sa(conviction as u64)
+        };
+
+        Self::record_vote(sender.clone(), proposal_id, supports_proposal, balance, weight, true)?;
+        <VoteConvictions<T>>::insert((proposal_id, sender.clone()), conviction);
+
+        if conviction > 0 {
+            let vote_lock_period = Self::dao_info(dao_id)?.vote_lock_period;
+            let lock_period = vote_lock_period * T::Moment::sa(1u64 << (conviction - 1));
+            Self::extend_conviction_lock(dao_id, sender.clone(), balance, Self::proposals(proposal_id).voting_deadline + lock_period);
         }
+
+        Self::deposit_event(RawEvent::VotedWithConviction(proposal_id, conviction, weight));
+
+        Ok(())
     }
 
-    fn unblock_me(origin) -> Result {
+    /// Vote on a quadratic proposal by spending `credits`, the vote weight is their
+    /// square root. The balance of the sender is its credits for all the quadratic
+    /// proposals open at the same time, the credits spent are locked until the deadlines.
+    fn vote_quadratic(origin, proposal_id: u64, supports_proposal: bool, credits: BalanceOf<T>) -> Result {
         let sender = ensure_signed(origin)?;
-        ensure!(Self::get_or_modify_blocked(sender), "can not modify blocked account");
+        ensure!(Self::voting_mode(proposal_id) == VotingMode::Quadratic, "This proposal is not quadratic");
+        ensure!(credits > <BalanceOf<T>>::sa(0), "credits should be more than zero");
+        let dao_id = Self::dao_of(proposal_id);
+        let now = Self::now(dao_id);
+
+        // the credits of the proposals past their deadline are free again, and voting again
+        // on this proposal replaces the credits spent on it
+        let mut commitments = Self::quadratic_commitments((dao_id, sender.clone()));
+        commitments.retain(|&(id, _)| id != proposal_id && now < Self::proposals(id).voting_deadline);
+        ensure!(commitments.len() < MAX_QUADRATIC_VOTES, "Too many open quadratic votes");
+        let committed = commitments.iter().fold(<BalanceOf<T>>::sa(0), |sum, &(_, c)| sum + c);
+        let available = Self::balance_of(dao_id, sender.clone()).checked_sub(&committed).unwrap_or_default();
+        ensure!(available >= credits, "Not enough credits");
+
+        let weight = Self::integer_sqrt(credits);
+        // a quadratic vote is personal, delegations do not apply
+        Self::record_vote(sender.clone(), proposal_id, supports_proposal, credits, weight, false)?;
+        commitments.push((proposal_id, credits));
+        <QuadraticCommitments<T>>::insert((dao_id, sender.clone()), commitments);
+        Self::extend_conviction_lock(dao_id, sender.clone(), committed + credits, Self::proposals(proposal_id).voting_deadline);
+
+        Self::deposit_event(RawEvent::VotedQuadratic(proposal_id, credits, weight));
+
         Ok(())
     }
-  }
-}
 
-// implementation of mudule
-// utility and private functions
-impl<T: Trait> Module<T> {
-    fn _unvote(sender: T::AccountId, proposal_id: u64) -> Result {
-        ensure!(
-            <timestamp::Module<T>>::get() < Self::proposals(proposal_id).voting_deadline,
-            "Already past voting deadling"
-        );
-        <Proposals<T>>::mutate(proposal_id, |p| {
-            if Self::vote_yes(sender.clone()) {
-                p.yea -= <token::Module<T>>::balance_of(sender.clone());
-                <VoteYes<T>>::insert(sender.clone(), false);
-            }
+    /// Commit a secret vote, `commitment` is the hash of (proposal_id, supports, salt).
+    /// The first commit locks `min_proposal_deposit` tokens, they are returned when the
+    /// vote is revealed and sent to the DAO treasury otherwise.
+    fn commit_vote(origin, proposal_id: u64, commitment: T::Hash) -> Result {
+        let sender = ensure_signed(origin)?;
+        ensure!(Self::voting_mode(proposal_id) == VotingMode::Secret, "This proposal is not secret");
+        let reveal_start = Self::reveal_start(proposal_id).ok_or("This proposal is not secret")?;
+        ensure!(Self::now_for(proposal_id) < reveal_start, "The commit period has ended");
+        ensure!(Self::proposals(proposal_id).open, "Proposal not open");
+        let dao_id = Self::dao_of(proposal_id);
+        ensure!(Self::balance_of(dao_id, sender.clone()) > <BalanceOf<T>>::sa(0), "This account doesn't hold the token");
 
-            if Self::vote_no(sender.clone()) {
-                p.nay -= <token::Module<T>>::balance_of(sender.clone());
-                <VoteNo<T>>::insert(sender.clone(), false);
+        let deposit = match Self::vote_commit((proposal_id, sender.clone())) {
+            Some((_, deposit)) => deposit,
+            None => {
+                let deposit = Self::dao_info(dao_id)?.min_proposal_deposit;
+                Self::lock_tokens(dao_id, sender.clone(), deposit, proposal_id)?;
+                <Committers<T>>::mutate(proposal_id, |c| c.push(sender.clone()));
+                deposit
             }
-        });
+        };
+        <VoteCommits<T>>::insert((proposal_id, sender.clone()), (commitment, deposit));
 
-        Ok(())
-    }
+        Self::deposit_event(RawEvent::VoteCommitted(proposal_id, sender));
 
-    fn close_proposal(proposal_id: u64) -> Result {
-        let mut p = Self::proposals(proposal_id).clone();
-        if p.open {
-            let sum = Self::sum_of_proposal_deposits();
-            let new_sum = sum
-                .checked_sub(&p.proposal_deposit)
-                .ok_or("Underflow when setting sum_of_proposal_deposits.")?;
-            <SumOfProposalDeposits<T>>::put(new_sum);
-        }
-        p.open = false;
-        <Proposals<T>>::insert(proposal_id, p);
         Ok(())
     }
 
-    // actualBalance must not underflow
-    fn actual_balance() -> T::TokenBalance {
-        let balance = <token::Module<T>>::balance_of(Self::curator().unwrap());
-        balance - Self::sum_of_proposal_deposits()
-    }
+    /// Reveal a secret vote during the reveal window. It weighs the sender's token
+    /// balance, which is locked until the voting deadline.
+    fn reveal_vote(origin, proposal_id: u64, supports_proposal: bool, salt: Vec<u8>) -> Result {
+        let sender = ensure_signed(origin)?;
+        let (commitment, deposit) = Self::vote_commit((proposal_id, sender.clone())).ok_or("No vote to reveal")?;
+        let reveal_start = Self::reveal_start(proposal_id).ok_or("This proposal is not secret")?;
+        let now = Self::now_for(proposal_id);
+        ensure!(now >= reveal_start, "The reveal window has not started");
+        ensure!(now < Self::proposals(proposal_id).voting_deadline, "Already past voting deadling");
+        let hash = <T as system::Trait>::Hashing::hash_of(&(proposal_id, supports_proposal, salt));
+        ensure!(hash == commitment, "The vote does not match the commitment");
+        let dao_id = Self::dao_of(proposal_id);
 
-    fn min_quorum(min_quorum_divisor: u64, value: T::TokenBalance) -> T::TokenBalance {
-        <token::Module<T>>::total_supply() / T::TokenBalance::sa(min_quorum_divisor.into())
-            + (value * <token::Module<T>>::total_supply())
-                / (T::TokenBalance::sa(3) * Self::actual_balance())
-    }
+        // the deposit is weighed with the balance, it is returned once the vote is counted
+        let balance = Self::balance_of(dao_id, sender.clone()) + deposit;
+        // a secret vote is personal, delegations do not apply
+        Self::record_vote(sender.clone(), proposal_id, supports_proposal, balance, balance, false)?;
+        <VoteCommits<T>>::remove((proposal_id, sender.clone()));
+        Self::unlock_tokens(dao_id, sender.clone(), deposit, proposal_id)?;
+        Self::extend_conviction_lock(dao_id, sender, balance, Self::proposals(proposal_id).voting_deadline);
 
-    fn get_or_modify_blocked(account: T::AccountId) -> bool {
-        if Self::blocked(account.clone()) == 0 {
-            return false;
+        Ok(())
+    }
+
+    /// Set the topic of a proposal, only its creator can do this before anyone voted.
+    fn set_proposal_topic(origin, proposal_id: u64, topic: u32) -> Result {
+        let sender = ensure_signed(origin)?;
+        let p = Self::proposals(proposal_id);
+        ensure!(p.creator == sender, "Only the creator can set the topic");
+        ensure!(p.open, "Proposal not open");
+        ensure!(Self::raw_tally(proposal_id) == Default::default(), "The proposal has already been voted on");
+
+        <ProposalTopics<T>>::insert(proposal_id, topic);
+
+        Ok(())
+    }
+
+    /// Delegate the sender's voting power in a DAO to `to`, for all proposals or only those
+    /// of `topic`. The delegate votes with it on every proposal the sender does not vote on,
+    /// the tokens it counted are locked until the voting deadline.
+    fn delegate(origin, dao_id: u64, to: T::AccountId, topic: Option<u32>) -> Result {
+        let sender = ensure_signed(origin)?;
+        ensure!(sender != to, "You can not delegate to yourself");
+        ensure!(Self::membership_of(dao_id).is_none(), "NFT votes can not be delegated");
+
+        // reject cycles
+        let mut next = Some(to.clone());
+        for _ in 0..MAX_DELEGATION_DEPTH {
+            match next {
+                Some(account) => {
+                    ensure!(account != sender, "Delegation would create a cycle");
+                    next = Self::effective_delegate(dao_id, account, topic);
+                }
+                None => break,
+            }
         }
-        let p = Self::proposals(Self::blocked(account.clone()));
-        if !p.open {
-            <Blocked<T>>::insert(account, 0);
-            return false;
-        } else {
-            return true;
+        let delegators = Self::delegators_of((dao_id, to.clone(), topic));
+        ensure!(delegators.len() < MAX_DELEGATORS || delegators.contains(&sender), "Too many delegators");
+
+        Self::remove_delegation(dao_id, sender.clone(), topic);
+        <Delegations<T>>::insert((dao_id, sender.clone(), topic), to.clone());
+        <Delegators<T>>::mutate((dao_id, to.clone(), topic), |d| d.push(sender.clone()));
+
+        Self::deposit_event(RawEvent::Delegated(dao_id, sender, to, topic));
+
+        Ok(())
+    }
+
+    fn undelegate(origin, dao_id: u64, topic: Option<u32>) -> Result {
+        let sender = ensure_signed(origin)?;
+        ensure!(Self::delegate_of((dao_id, sender.clone(), topic)).is_some(), "No delegation for this topic");
+
+        Self::remove_delegation(dao_id, sender.clone(), topic);
+
+        Self::deposit_event(RawEvent::Undelegated(dao_id, sender, topic));
+
+        Ok(())
+    }
+
+    /// Release the tokens locked by conviction votes once the lock has expired.
+    fn unlock(origin, dao_id: u64) -> Result {
+        let sender = ensure_signed(origin)?;
+        let (locked, until) = Self::conviction_lock((dao_id, sender.clone())).ok_or("No tokens are locked")?;
+        ensure!(Self::now(dao_id) >= until, "The lock has not expired yet");
+
+        <ConvictionLocks<T>>::remove((dao_id, sender.clone()));
+        Self::remove_vote_lock(dao_id, sender.clone());
+
+        Self::deposit_event(RawEvent::Unlocked(dao_id, sender, locked));
+
+        Ok(())
+    }
+
+    /// Proposals are executed automatically at their deadline, this is the manual
+    /// fallback in case the scheduled execution failed or has not happened yet.
+    fn execute_proposal(origin, proposal_id: u64, transaction_data: Vec<u8>) -> Result{
+        ensure_signed(origin)?;
+        if let Some(end) = Self::challenge_end(proposal_id) {
+            ensure!(Self::now_for(proposal_id) >= end, "The challenge period has not ended yet");
+            return Self::execute_optimistic(proposal_id);
+        }
+        match Self::grace_period_end(proposal_id) {
+            Some(end) => {
+                ensure!(Self::now_for(proposal_id) >= end, "The grace period has not ended yet");
+                Self::try_enact(proposal_id)
+            }
+            None => Self::execute_proposal_impl(proposal_id, transaction_data),
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Leave the DAO during the grace period of a passed proposal the sender did not
+    /// vote yes on. The sender's tokens are burned and its pro-rata share of the DAO
+    /// funds and of the treasury is paid out.
+    fn ragequit(origin, proposal_id: u64) -> Result {
+        let sender = ensure_signed(origin)?;
+        let end = Self::grace_period_end(proposal_id).ok_or("The proposal is not in its grace period")?;
+        ensure!(Self::now_for(proposal_id) < end, "The grace period has ended");
+        ensure!(!Self::vote_yes((proposal_id, sender.clone())), "Yes voters can not ragequit");
+        if let Some((voter, _)) = Self::delegated_vote((proposal_id, sender.clone())) {
+            ensure!(!Self::vote_yes((proposal_id, voter)), "Yes voters can not ragequit");
+        }
+        let dao_id = Self::dao_of(proposal_id);
+        ensure!(!Self::get_or_modify_blocked(dao_id, sender.clone()), "This is blocked");
+        if let Some((_, until)) = Self::conviction_lock((dao_id, sender.clone())) {
+            ensure!(Self::now(dao_id) >= until, "The tokens are locked by a conviction vote");
+        }
+        // the tokens of a delegator still count in the tally of an open proposal
+        let counted = Self::delegated_proposals((dao_id, sender.clone()))
+            .into_iter()
+            .any(|id| Self::proposals(id).open && Self::delegated_vote((id, sender.clone())).is_some());
+        ensure!(!counted, "The tokens are counted in an open vote");
 
-    use primitives::{Blake2Hasher, H256};
-    use runtime_io::with_externalities;
-    use runtime_primitives::{
-        testing::{Digest, DigestItem, Header, UintAuthorityId},
-        traits::{BlakeTwo256, IdentityLookup},
-        BuildStorage,
-    };
-    use support::{assert_noop, assert_ok, impl_outer_origin};
+        let zero = <BalanceOf<T>>::sa(0);
+        let tokens = Self::balance_of(dao_id, sender.clone());
+        ensure!(tokens > zero, "This account doesn't hold the token");
+        let total_supply = Self::total_supply(dao_id);
+        let funds = Self::token_funds(dao_id) * tokens / total_supply;
+        let treasury_funds = Self::actual_balance(dao_id) * tokens / total_supply;
 
-    impl_outer_origin! {
-        pub enum Origin for Test {}
+        // the native transfer can fail, it comes before the tokens are burned
+        if treasury_funds > zero {
+            T::Currency::transfer(&Self::treasury_account(dao_id), &sender, treasury_funds)?;
+        }
+        Self::burn_tokens(dao_id, sender.clone(), tokens)?;
+        if funds > zero {
+            Self::transfer_tokens(dao_id, Self::treasury_account(dao_id), sender.clone(), funds)?;
+        }
+        // the lock has expired
+        <ConvictionLocks<T>>::remove((dao_id, sender.clone()));
+        <DelegatedProposals<T>>::remove((dao_id, sender.clone()));
+        Self::remove_vote_lock(dao_id, sender.clone());
+
+        Self::deposit_event(RawEvent::Ragequit(proposal_id, sender, tokens, funds, treasury_funds));
+
+        Ok(())
     }
 
-    // For testing the module, we construct most of a mock runtime. This means
-    // first constructing a configuration type (`Test`) which `impl`s each of the
-    // configuration traits of modules we want to use.
-    #[derive(Clone, Eq, PartialEq)]
-    pub struct Test;
-    impl system::Trait for Test {
-        type Origin = Origin;
-        type Index = u64;
-        type BlockNumber = u64;
-        type Hash = H256;
-        type Hashing = BlakeTwo256;
-        type Digest = Digest;
-        type AccountId = u64;
-        type Lookup = IdentityLookup<u64>;
-        type Header = Header;
-        type Event = ();
-        type Log = DigestItem;
+    /// A curator action, it is applied once `threshold` council members called it with
+    /// the same arguments.
+    fn change_min_proposal_deposit(origin, dao_id: u64, new_min_proposal_deposit: BalanceOf<T>) -> Result{
+        let sender = ensure_signed(origin)?;
+        let dao = Self::dao_info(dao_id)?;
+        if new_min_proposal_deposit > Self::token_funds(dao_id) / <BalanceOf<T>>::sa(dao.max_deposit_divisor.into())
+        {
+            return Err("change_proposal_deposit failed");
+        }
+        if !Self::approve_curator_action(dao_id, sender, CuratorAction::ChangeMinProposalDeposit(new_min_proposal_deposit))? {
+            return Ok(());
+        }
+
+        <Daos<T>>::mutate(dao_id, |dao| {
+            if let Some(dao) = dao {
+                dao.min_proposal_deposit = new_min_proposal_deposit;
+            }
+        });
+        Ok(())
     }
-    impl consensus::Trait for Test {
-        type Log = DigestItem;
-        type SessionKey = UintAuthorityId;
-        type InherentOfflineReport = ();
+
+    /// A curator action, see `change_min_proposal_deposit`.
+    fn change_allowed_recipients(origin, dao_id: u64, recipient: T::AccountId, allowed: bool) ->Result{
+        let sender = ensure_signed(origin)?;
+        if !Self::approve_curator_action(dao_id, sender, CuratorAction::ChangeAllowedRecipient(recipient.clone(), allowed))? {
+            return Ok(());
+        }
+
+        <AllowedRecipients<T>>::insert((dao_id, recipient.clone()), allowed);
+        Self::deposit_event(RawEvent::AllowedRecipientChanged(dao_id, recipient, allowed));
+        Ok(())
     }
-    impl token::Trait for Test {
-        type Event = ();
-        type TokenBalance = u64;
+
+    /// Allow or disallow `recipient` in the DAO which passed the call proposal of this call.
+    fn set_allowed_recipient(origin, recipient: T::AccountId, allowed: bool) -> Result {
+        let dao_id = T::ExternalOrigin::ensure_origin(origin)?;
+        Self::dao_info(dao_id)?;
+
+        <AllowedRecipients<T>>::insert((dao_id, recipient.clone()), allowed);
+        Self::deposit_event(RawEvent::AllowedRecipientChanged(dao_id, recipient, allowed));
+        Ok(())
     }
-    impl timestamp::Trait for Test {
-        type Moment = u64;
-        type OnTimestampSet = ();
+
+    /// Set the minimum proposal deposit of the DAO which passed the call proposal of this call.
+    fn set_min_proposal_deposit(origin, min_proposal_deposit: BalanceOf<T>) -> Result {
+        let dao_id = T::ExternalOrigin::ensure_origin(origin)?;
+        let dao = Self::dao_info(dao_id)?;
+        ensure!(
+            min_proposal_deposit <= Self::token_funds(dao_id) / <BalanceOf<T>>::sa(dao.max_deposit_divisor),
+            "The deposit is more than the funds allow"
+        );
+
+        <Daos<T>>::mutate(dao_id, |dao| {
+            if let Some(dao) = dao {
+                dao.min_proposal_deposit = min_proposal_deposit;
+            }
+        });
+        Ok(())
     }
-    impl Trait for Test {
-        type Event = ();
+
+    /// Pay `amount` tokens, or native currency if `native`, to `to` from the treasury of the
+    /// DAO which passed the call proposal of this call.
+    fn spend(origin, to: T::AccountId, amount: BalanceOf<T>, native: bool) -> Result {
+        let dao_id = T::ExternalOrigin::ensure_origin(origin)?;
+        Self::dao_info(dao_id)?;
+
+        if native {
+            T::Currency::transfer(&Self::treasury_account(dao_id), &to, amount)?;
+        } else {
+            Self::transfer_tokens(dao_id, Self::treasury_account(dao_id), to.clone(), amount)?;
+        }
+        Self::deposit_event(RawEvent::TreasurySpent(dao_id, to, amount, native));
+        Ok(())
     }
-    type Dao = Module<Test>;
-    type Token = token::Module<Test>;
-    type Timestamp = timestamp::Module<Test>;
 
-    fn init() -> Result {
-        Dao::init(Origin::signed(1))
+    /// Anyone can halve the quorum when it has not been met for `quorum_havling_period`,
+    /// the curator council can do it earlier.
+    fn halvemin_quorum(origin, dao_id: u64) -> Result {
+        let sender = ensure_signed(origin)?;
+        let now = Self::now(dao_id);
+        let dao = Self::dao_info(dao_id)?;
+
+        let period_passed = dao.last_time_min_quorum_met < (now.clone() - dao.quorum_havling_period);
+        let can_halve = dao.last_time_min_quorum_met < (now.clone() - dao.min_proposal_debate_period)
+            && dao.proposal_count > 0;
+        let by_curator = if !period_passed && can_halve && dao.council.contains(&sender) {
+            if !Self::approve_curator_action(dao_id, sender, CuratorAction::HalveMinQuorum)? {
+                return Ok(());
+            }
+            true
+        } else {
+            false
+        };
+
+        if (period_passed || by_curator) && can_halve {
+            <Daos<T>>::mutate(dao_id, |dao| {
+                if let Some(dao) = dao {
+                    dao.last_time_min_quorum_met = now;
+                    dao.min_quorum_divisor *= 2u64;
+                }
+            });
+            return Ok(());
+        } else {
+            return Err("halvemin_quorum failed.");
+        }
     }
 
-    // builds the genesis config store and sets mock values
-    fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
-        let mut t = system::GenesisConfig::<Test>::default()
-            .build_storage()
-            .unwrap()
-            .0;
-        t.extend(
-            token::GenesisConfig::<Test> {
-                total_supply: 21000000,
-                name: "ABMatrix Token".as_bytes().into(),
-                symbol: "ABT".as_bytes().into(),
-                decimal: 18,
+    /// Propose a bounty with a `budget` in native currency, managed by `curator`. If the
+    /// proposal passes, the budget moves from the treasury to the bounty account.
+    fn new_bounty_proposal(
+        origin,
+        dao_id: u64,
+        curator: T::AccountId,
+        budget: BalanceOf<T>,
+        review_delay: T::Moment,
+        description: Vec<u8>,
+        debating_period: T::Moment,
+        deposit: BalanceOf<T>
+    ) -> Result {
+        let sender = ensure_signed(origin)?;
+        ensure!(Self::balance_of(dao_id, sender.clone()) > <BalanceOf<T>>::sa(0), "This account doesn't hold the token");
+        ensure!(budget > <BalanceOf<T>>::sa(0), "The budget can not be zero");
+
+        let bounty_id = Self::bounty_count();
+        let proposal_id = Self::proposal_count();
+        Self::create_proposal(dao_id, sender, Self::bounty_account(bounty_id), budget, description.clone(), bounty_id.encode(), debating_period, deposit, false)?;
+        <NativePayouts<T>>::insert(proposal_id, true);
+        <BountyProposals<T>>::insert(proposal_id, bounty_id);
+        <Bounties<T>>::insert(bounty_id, Bounty {
+            dao_id,
+            curator,
+            budget,
+            review_delay,
+            description,
+            status: BountyStatus::Proposed,
+        });
+        <BountyCount<T>>::put(bounty_id + 1);
+
+        Self::deposit_event(RawEvent::BountyProposed(bounty_id, proposal_id));
+        Ok(())
+    }
+
+    /// The curator assigns an active bounty to `contributor`. Assigning it again before the
+    /// work is submitted replaces the contributor.
+    fn assign_bounty(origin, bounty_id: u64, contributor: T::AccountId) -> Result {
+        let sender = ensure_signed(origin)?;
+        let mut bounty = Self::bounties(bounty_id).ok_or("This bounty does not exist")?;
+        ensure!(bounty.curator == sender, "Only the bounty curator can do this");
+        match bounty.status {
+            BountyStatus::Proposed => return Err("The bounty is not funded yet"),
+            BountyStatus::Active | BountyStatus::Assigned(_) => {}
+            _ => return Err("The work was already submitted"),
+        }
+
+        bounty.status = BountyStatus::Assigned(contributor.clone());
+        <Bounties<T>>::insert(bounty_id, bounty);
+
+        Self::deposit_event(RawEvent::BountyAssigned(bounty_id, contributor));
+        Ok(())
+    }
+
+    /// The contributor submits its work, `work` describes it for the curator.
+    fn submit_bounty(origin, bounty_id: u64, work: Vec<u8>) -> Result {
+        let sender = ensure_signed(origin)?;
+        let mut bounty = Self::bounties(bounty_id).ok_or("This bounty does not exist")?;
+        ensure!(bounty.status == BountyStatus::Assigned(sender.clone()), "The bounty is not assigned to you");
+        ensure!(!work.is_empty(), "The work can not be empty");
+
+        bounty.status = BountyStatus::Submitted(sender.clone());
+        <Bounties<T>>::insert(bounty_id, bounty);
+
+        Self::deposit_event(RawEvent::BountySubmitted(bounty_id, sender));
+        Ok(())
+    }
+
+    /// The curator accepts the submitted work, the budget can be claimed by the
+    /// contributor after the review delay.
+    fn accept_bounty(origin, bounty_id: u64) -> Result {
+        let sender = ensure_signed(origin)?;
+        let mut bounty = Self::bounties(bounty_id).ok_or("This bounty does not exist")?;
+        ensure!(bounty.curator == sender, "Only the bounty curator can do this");
+        let contributor = match bounty.status {
+            BountyStatus::Submitted(ref contributor) => contributor.clone(),
+            _ => return Err("The work is not submitted"),
+        };
+
+        let unlock_at = Self::now(bounty.dao_id) + bounty.review_delay.clone();
+        bounty.status = BountyStatus::PendingPayout(contributor.clone(), unlock_at.clone());
+        <Bounties<T>>::insert(bounty_id, bounty);
+
+        Self::deposit_event(RawEvent::BountyAccepted(bounty_id, contributor, unlock_at));
+        Ok(())
+    }
+
+    /// Pay the budget of an accepted bounty to its contributor after the review delay.
+    fn claim_bounty(origin, bounty_id: u64) -> Result {
+        ensure_signed(origin)?;
+        let bounty = Self::bounties(bounty_id).ok_or("This bounty does not exist")?;
+        let contributor = match bounty.status {
+            BountyStatus::PendingPayout(ref contributor, ref unlock_at) => {
+                ensure!(Self::now(bounty.dao_id) >= *unlock_at, "The review delay has not passed");
+                contributor.clone()
             }
-            .build_storage()
-            .unwrap()
-            .0,
-        );
-        t.extend(
-            GenesisConfig::<Test> {
-                curator: 1,
-                min_proposal_deposit: 100,
-                min_quorum_divisor: 7,
-                min_proposal_debate_period: 14,
-                quorum_havling_period: 175,
-                execute_proposal_period: 10,
-                max_deposit_divisor: 100,
+            _ => return Err("The work is not accepted"),
+        };
+
+        T::Currency::transfer(&Self::bounty_account(bounty_id), &contributor, bounty.budget)?;
+        <Bounties<T>>::remove(bounty_id);
+
+        Self::deposit_event(RawEvent::BountyPaid(bounty_id, contributor, bounty.budget));
+        Ok(())
+    }
+
+    /// The curator cancels an active bounty which is not assigned, its budget returns to
+    /// the DAO treasury.
+    fn cancel_bounty(origin, bounty_id: u64) -> Result {
+        let sender = ensure_signed(origin)?;
+        let bounty = Self::bounties(bounty_id).ok_or("This bounty does not exist")?;
+        ensure!(bounty.curator == sender, "Only the bounty curator can do this");
+        ensure!(bounty.status == BountyStatus::Active, "Only an unassigned bounty can be cancelled");
+
+        Self::refund_bounty(bounty_id, bounty)
+    }
+
+    /// Cancel a funded bounty of the DAO which passed the call proposal of this call, in
+    /// any state before it is paid. Its budget returns to the DAO treasury.
+    fn close_bounty(origin, bounty_id: u64) -> Result {
+        let dao_id = T::ExternalOrigin::ensure_origin(origin)?;
+        let bounty = Self::bounties(bounty_id).ok_or("This bounty does not exist")?;
+        ensure!(bounty.dao_id == dao_id, "The bounty belongs to another DAO");
+        ensure!(bounty.status != BountyStatus::Proposed, "The bounty is not funded yet");
+
+        Self::refund_bounty(bounty_id, bounty)
+    }
+
+    /// Replace the curator of a bounty of the DAO which passed the call proposal of this call.
+    fn set_bounty_curator(origin, bounty_id: u64, curator: T::AccountId) -> Result {
+        let dao_id = T::ExternalOrigin::ensure_origin(origin)?;
+        let mut bounty = Self::bounties(bounty_id).ok_or("This bounty does not exist")?;
+        ensure!(bounty.dao_id == dao_id, "The bounty belongs to another DAO");
+
+        bounty.curator = curator.clone();
+        <Bounties<T>>::insert(bounty_id, bounty);
+
+        Self::deposit_event(RawEvent::BountyCuratorChanged(bounty_id, curator));
+        Ok(())
+    }
+
+    /// Add `amount` tokens of the DAO, or native currency if `native`, to its reward pool.
+    fn fund_rewards(origin, dao_id: u64, amount: BalanceOf<T>, native: bool) -> Result {
+        let sender = ensure_signed(origin)?;
+        Self::dao_info(dao_id)?;
+        ensure!(amount > <BalanceOf<T>>::sa(0), "The amount can not be zero");
+
+        if native {
+            T::Currency::transfer(&sender, &Self::reward_account(dao_id), amount)?;
+        } else {
+            let locked = Self::conviction_lock((dao_id, sender.clone())).map(|l| l.0).unwrap_or_default();
+            let unlocked = Self::balance_of(dao_id, sender.clone()).checked_sub(&locked).unwrap_or_default();
+            ensure!(unlocked >= amount, "Not enough unlocked balance.");
+            Self::transfer_tokens(dao_id, sender.clone(), Self::reward_account(dao_id), amount)?;
+        }
+
+        Self::deposit_event(RawEvent::RewardsFunded(dao_id, sender, amount, native));
+        Ok(())
+    }
+
+    /// Claim the sender's share of the rewards of a closed proposal it voted on, in
+    /// proportion to its vote weight.
+    fn claim_reward(origin, proposal_id: u64) -> Result {
+        let sender = ensure_signed(origin)?;
+        let (tokens, native, total_weight, expiry) = Self::proposal_reward(proposal_id).ok_or("No rewards for this proposal")?;
+        ensure!(Self::now_for(proposal_id) < expiry, "The claim period has ended");
+        ensure!(!Self::reward_claimed((proposal_id, sender.clone())), "The reward was already claimed");
+        let weight = Self::vote_weight((proposal_id, sender.clone()));
+        ensure!(weight > <BalanceOf<T>>::sa(0), "Only voters can claim a reward");
+
+        let dao_id = Self::dao_of(proposal_id);
+        let reward_account = Self::reward_account(dao_id);
+        let token_share = tokens * weight / total_weight;
+        let native_share = native * weight / total_weight;
+
+        // paid before the bookkeeping, a failed transfer leaves the reward claimable
+        let zero = <BalanceOf<T>>::sa(0);
+        if token_share > zero {
+            ensure!(
+                Self::balance_of(dao_id, reward_account.clone()) >= token_share,
+                "Not enough tokens in the reward pool"
+            );
+        }
+        if native_share > zero {
+            T::Currency::transfer(&reward_account, &sender, native_share)?;
+        }
+        if token_share > zero {
+            Self::transfer_tokens(dao_id, reward_account, sender.clone(), token_share)?;
+        }
+
+        <RewardClaimed<T>>::insert((proposal_id, sender.clone()), true);
+        <RewardsReserved<T>>::mutate(dao_id, |r| {
+            r.0 -= token_share;
+            r.1 -= native_share;
+        });
+        <UnclaimedRewards<T>>::mutate(proposal_id, |r| {
+            r.0 -= token_share;
+            r.1 -= native_share;
+        });
+
+        Self::deposit_event(RawEvent::RewardClaimed(proposal_id, sender, token_share, native_share));
+        Ok(())
+    }
+
+    fn unblock_me(origin, dao_id: u64) -> Result {
+        let sender = ensure_signed(origin)?;
+        ensure!(Self::get_or_modify_blocked(dao_id, sender), "can not modify blocked account");
+        Ok(())
+    }
+
+    /// Leave the DAO after a yes vote on a `new_curator` proposal. The first split creates
+    /// the child DAO, see `create_child_dao`. The sender's tokens are burned and minted as
+    /// child DAO tokens, and its pro-rata share of the DAO funds moves to the treasury of
+    /// the child DAO: the token funds as child DAO tokens, the native currency as is.
+    fn split_dao(origin, proposal_id: u64) -> Result {
+        let sender = ensure_signed(origin)?;
+        let dao_id = Self::dao_of(proposal_id);
+        let dao = Self::dao_info(dao_id)?;
+        let p = Self::proposals(proposal_id);
+        let now = Self::now(dao_id);
+
+        ensure!(p.new_curator, "Not a new curator proposal");
+        ensure!(now >= p.voting_deadline, "It has not yet reached the voting deadline.");
+        ensure!(now <= p.voting_deadline.clone() + dao.execute_proposal_period, "The split period has passed.");
+        ensure!(Self::vote_yes((proposal_id, sender.clone())), "Only yes voters can split");
+        ensure!(Self::blocked((dao_id, sender.clone())) == proposal_id || !Self::get_or_modify_blocked(dao_id, sender.clone()), "This is blocked");
+
+        // The first split creates the child DAO from the current state of the DAO
+        let child = match Self::child_daos(proposal_id) {
+            Some(child) => child,
+            None => {
+                if p.open {
+                    Self::close_proposal(proposal_id)?;
+                    Self::unlock_tokens(dao_id, p.creator.clone(), p.proposal_deposit, proposal_id)?;
+                }
+                let child = ChildDao {
+                    dao_id: Self::create_child_dao(&dao, p.recipient.clone())?,
+                    split_balance: Self::token_funds(dao_id),
+                    split_treasury: Self::actual_balance(dao_id),
+                    split_total_supply: Self::total_supply(dao_id),
+                };
+                <ChildDaos<T>>::insert(proposal_id, child.clone());
+                child
             }
-            .build_storage()
-            .unwrap()
-            .0,
-        );
-        t.into()
+        };
+
+        let tokens = Self::balance_of(dao_id, sender.clone());
+        ensure!(tokens > <BalanceOf<T>>::sa(0), "This account doesn't hold the token");
+        let funds = child.split_balance * tokens / child.split_total_supply;
+        let treasury_funds = child.split_treasury * tokens / child.split_total_supply;
+        let child_token = Self::token_of(child.dao_id).ok_or("The child DAO has no token")?;
+        let child_treasury = Self::treasury_account(child.dao_id);
+
+        Self::burn_tokens(dao_id, sender.clone(), tokens)?;
+        Self::burn_tokens(dao_id, Self::treasury_account(dao_id), funds)?;
+        <lockabletoken::Module<T>>::mint(child_token, sender.clone(), tokens)?;
+        <lockabletoken::Module<T>>::mint(child_token, child_treasury.clone(), funds)?;
+        if treasury_funds > <BalanceOf<T>>::sa(0) {
+            T::Currency::transfer(&Self::treasury_account(dao_id), &child_treasury, treasury_funds)?;
+        }
+        // the locked tokens leave to the child DAO
+        <ConvictionLocks<T>>::remove((dao_id, sender.clone()));
+        Self::remove_vote_lock(dao_id, sender.clone());
+        <VoteYes<T>>::insert((proposal_id, sender.clone()), false);
+
+        Self::deposit_event(RawEvent::DaoSplit(proposal_id, sender, child.dao_id, tokens, funds, treasury_funds));
+
+        Ok(())
     }
+  }
+}
 
-    #[test]
+// implementation of mudule
+// utility and private functions
+impl<T: Trait> Module<T> {
+    fn dao_info(dao_id: u64) -> result::Result<DaoInfoOf<T>, &'static str> {
+        Self::daos(dao_id).ok_or("This DAO does not exist")
+    }
+
+    /// Record the approval of `who` for `action`, returns whether the threshold of the
+    /// council is reached and the action must be applied.
+    fn approve_curator_action(
+        dao_id: u64,
+        who: T::AccountId,
+        action: CuratorAction<T::AccountId, BalanceOf<T>>,
+    ) -> result::Result<bool, &'static str> {
+        let dao = Self::dao_info(dao_id)?;
+        ensure!(dao.council.contains(&who), "Only the curator council can do this");
+
+        let action_hash = (dao_id, action).using_encoded(<T as system::Trait>::Hashing::hash);
+        let mut approvals = Self::curator_approvals((dao_id, action_hash));
+        ensure!(!approvals.contains(&who), "You already approved this action");
+        approvals.push(who.clone());
+        let count = approvals.len() as u32;
+
+        Self::deposit_event(RawEvent::CuratorActionApproved(dao_id, who, action_hash, count));
+
+        if count >= dao.threshold {
+            <CuratorApprovals<T>>::remove((dao_id, action_hash));
+            <PendingCuratorActions<T>>::mutate(dao_id, |pending| pending.retain(|h| *h != action_hash));
+            Self::deposit_event(RawEvent::CuratorActionExecuted(dao_id, action_hash));
+            Ok(true)
+        } else {
+            if count == 1 {
+                <PendingCuratorActions<T>>::mutate(dao_id, |pending| pending.push(action_hash));
+            }
+            <CuratorApprovals<T>>::insert((dao_id, action_hash), approvals);
+            Ok(false)
+        }
+    }
+
+    /// Replace the council, the approvals given by the previous council are dropped. Its
+    /// first member becomes the curator.
+    fn set_council(dao_id: u64, council: Vec<T::AccountId>, threshold: u32) {
+        for action_hash in Self::pending_curator_actions(dao_id) {
+            <CuratorApprovals<T>>::remove((dao_id, action_hash));
+        }
+        <PendingCuratorActions<T>>::remove(dao_id);
+        <Daos<T>>::mutate(dao_id, |dao| {
+            if let Some(dao) = dao {
+                dao.curator = council[0].clone();
+                dao.council = council.clone();
+                dao.threshold = threshold;
+            }
+        });
+
+        Self::deposit_event(RawEvent::CouncilChanged(dao_id, council, threshold));
+    }
+
+    /// Create the DAO of the members splitting off with a `new_curator` proposal, with the
+    /// parameters of the `parent` DAO. It is governed by a new `lockabletoken`, minted by
+    /// `split_dao` as the members join, and `curator` is its curator and council.
+    fn create_child_dao(parent: &DaoInfoOf<T>, curator: T::AccountId) -> result::Result<u64, &'static str> {
+        let dao_id = Self::dao_count();
+        let token_id = CHILD_TOKEN_BASE | dao_id;
+        <lockabletoken::Module<T>>::create_token(
+            curator.clone(),
+            token_id,
+            b"Child DAO".to_vec(),
+            b"CDAO".to_vec(),
+            <BalanceOf<T>>::sa(0),
+            0,
+        )?;
+
+        let mut council = Vec::new();
+        council.push(curator.clone());
+        <Daos<T>>::insert(dao_id, DaoInfo {
+            curator: curator.clone(),
+            council,
+            threshold: 1,
+            token: Some(token_id),
+            membership: None,
+            last_time_min_quorum_met: parent.clock.now::<T>(),
+            sum_of_proposal_deposits: <BalanceOf<T>>::sa(0),
+            proposal_count: 0,
+            ..parent.clone()
+        });
+        <DaoCount<T>>::put(dao_id + 1);
+        <AllowedRecipients<T>>::insert((dao_id, curator.clone()), true);
+
+        Self::deposit_event(RawEvent::DaoCreated(dao_id, curator, token_id));
+        Ok(dao_id)
+    }
+
+    fn token_of(dao_id: u64) -> Option<u64> {
+        Self::daos(dao_id).and_then(|dao| dao.token)
+    }
+
+    fn clock_of(dao_id: u64) -> Clock {
+        Self::daos(dao_id).map(|dao| dao.clock).unwrap_or_default()
+    }
+
+    /// The current time on the clock of the DAO.
+    pub fn now(dao_id: u64) -> T::Moment {
+        Self::clock_of(dao_id).now::<T>()
+    }
+
+    /// The current time on the clock of the DAO of `proposal_id`.
+    fn now_for(proposal_id: u64) -> T::Moment {
+        Self::now(Self::dao_of(proposal_id))
+    }
+
+    fn membership_of(dao_id: u64) -> Option<NftMembershipOf<T>> {
+        Self::daos(dao_id).and_then(|dao| dao.membership)
+    }
+
+    /// Vote weight of `who`: its governance token balance, or the weight of its NFTs of
+    /// the collection of an NFT gated DAO.
+    pub fn voting_balance(dao_id: u64, who: T::AccountId) -> BalanceOf<T> {
+        match Self::membership_of(dao_id) {
+            None => Self::balance_of(dao_id, who),
+            Some(NftMembership::PerToken(issuer)) => {
+                <BalanceOf<T>>::sa(<erc721::Module<T>>::issued_balance_of((who, issuer)))
+            }
+            Some(NftMembership::Attribute(issuer, key)) => {
+                <BalanceOf<T>>::sa(<erc721::Module<T>>::issued_attribute_of((who, issuer, key)))
+            }
+        }
+    }
+
+    /// Sum of the vote weights of all the accounts, see `voting_balance`.
+    pub fn voting_supply(dao_id: u64) -> BalanceOf<T> {
+        match Self::membership_of(dao_id) {
+            None => Self::total_supply(dao_id),
+            Some(NftMembership::PerToken(issuer)) => <BalanceOf<T>>::sa(<erc721::Module<T>>::issued_supply(issuer)),
+            Some(NftMembership::Attribute(issuer, key)) => {
+                <BalanceOf<T>>::sa(<erc721::Module<T>>::issued_attribute_total((issuer, key)))
+            }
+        }
+    }
+
+    /// Balance of `who` in the governance token of the DAO.
+    pub fn balance_of(dao_id: u64, who: T::AccountId) -> BalanceOf<T> {
+        match Self::token_of(dao_id) {
+            None => <token::Module<T>>::balance_of(who),
+            Some(token_id) => <lockabletoken::Module<T>>::balance_of((token_id, who)),
+        }
+    }
+
+    pub fn total_supply(dao_id: u64) -> BalanceOf<T> {
+        match Self::token_of(dao_id) {
+            None => <token::Module<T>>::total_supply(),
+            Some(token_id) => <lockabletoken::Module<T>>::total_supply(token_id),
+        }
+    }
+
+    fn lock_tokens(dao_id: u64, from: T::AccountId, value: BalanceOf<T>, proposal_id: u64) -> Result {
+        match Self::token_of(dao_id) {
+            None => <token::Module<T>>::lock(from, value, proposal_id),
+            Some(token_id) => <lockabletoken::Module<T>>::lock_deposit(token_id, from, value, proposal_id),
+        }
+    }
+
+    fn unlock_tokens(dao_id: u64, to: T::AccountId, value: BalanceOf<T>, proposal_id: u64) -> Result {
+        match Self::token_of(dao_id) {
+            None => <token::Module<T>>::unlock(to, value, proposal_id),
+            Some(token_id) => <lockabletoken::Module<T>>::unlock_deposit(token_id, to, value, proposal_id),
+        }
+    }
+
+    fn transfer_tokens(dao_id: u64, from: T::AccountId, to: T::AccountId, value: BalanceOf<T>) -> Result {
+        match Self::token_of(dao_id) {
+            None => <token::Module<T>>::transfer_impl(from, to, value),
+            Some(token_id) => <lockabletoken::Module<T>>::transfer_impl(token_id, from, to, value),
+        }
+    }
+
+    fn burn_tokens(dao_id: u64, from: T::AccountId, value: BalanceOf<T>) -> Result {
+        match Self::token_of(dao_id) {
+            None => <token::Module<T>>::burn(from, value),
+            Some(token_id) => <lockabletoken::Module<T>>::burn(token_id, from, value),
+        }
+    }
+
+    fn set_vote_lock(dao_id: u64, who: T::AccountId, value: BalanceOf<T>) {
+        if let Some(membership) = Self::membership_of(dao_id) {
+            // the votes of an NFT gated DAO lock the NFTs of its collection, not the
+            // governance tokens
+            return <erc721::Module<T>>::set_vote_lock(who, membership.issuer(), dao_id);
+        }
+        match Self::token_of(dao_id) {
+            None => <token::Module<T>>::set_vote_lock(who, value),
+            Some(token_id) => <lockabletoken::Module<T>>::set_vote_lock(token_id, dao_id, who, value),
+        }
+    }
+
+    fn remove_vote_lock(dao_id: u64, who: T::AccountId) {
+        if let Some(membership) = Self::membership_of(dao_id) {
+            return <erc721::Module<T>>::remove_vote_lock(who, membership.issuer(), dao_id);
+        }
+        match Self::token_of(dao_id) {
+            None => <token::Module<T>>::remove_vote_lock(who),
+            Some(token_id) => <lockabletoken::Module<T>>::remove_vote_lock(token_id, dao_id, who),
+        }
+    }
+
+    fn execute_proposal_impl(proposal_id: u64, transaction_data: Vec<u8>) -> Result {
+        let dao_id = Self::dao_of(proposal_id);
+        let dao = Self::dao_info(dao_id)?;
+        let p = Self::proposals(proposal_id);
+        let now = Self::now(dao_id);
+
+        if p.open && now > p.voting_deadline.clone() + dao.execute_proposal_period {
+            Self::close_proposal(proposal_id)?;
+            if p.new_curator {
+                // nobody split during the split period, the deposit is returned
+                Self::unlock_tokens(dao_id, p.creator.clone(), p.proposal_deposit, proposal_id)?;
+                return Ok(());
+            }
+            let quorum_met = Self::quorum_of(proposal_id) >= Self::min_quorum(dao_id, dao.min_quorum_divisor, p.amount, Self::pays_native(proposal_id));
+            Self::settle_deposit(proposal_id, quorum_met)?;
+            ensure!(false,"The execution deadline has passed.");
+        }
+
+        ensure!(now >= p.voting_deadline, "It has not yet reached the voting deadline.");
+        ensure!(p.open, "Proposal not open");
+        ensure!(!p.proposal_passed, "Proposal has already been passed");
+        ensure!(!p.new_curator, "A new curator proposal is executed by split_dao");
+
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&p.amount.encode());
+        buf.extend_from_slice(&proposal_id.encode());
+        buf.extend_from_slice(&p.recipient.encode());
+        buf.extend_from_slice(&transaction_data);
+        let proposal_hash = <T as system::Trait>::Hashing::hash(&buf[..]);
+
+        ensure!(p.proposal_hash == proposal_hash, "Not match the proposal hash");
+
+        let is_call = <ProposalCalls<T>>::exists(proposal_id);
+        let is_council = <CouncilProposals<T>>::exists(proposal_id);
+        let is_parameter = <ParameterProposals<T>>::exists(proposal_id);
+        let is_bounty = <BountyProposals<T>>::exists(proposal_id);
+
+        // the recipient of a call, council or parameter proposal is only its creator, the
+        // one of a bounty proposal is the bounty account
+        if !is_call && !is_council && !is_parameter && !is_bounty && !Self::allowed_recipients((dao_id, p.recipient.clone())) {
+            Self::close_proposal(proposal_id)?;
+            Self::unlock_tokens(dao_id, p.creator.clone(), p.proposal_deposit, proposal_id)?;
+            return Err("No such recipient in the whitelist.");
+        }
+
+        let mut proposal_check = true;
+        let pays_native = Self::pays_native(proposal_id);
+        let funds = if pays_native { Self::actual_balance(dao_id) } else { Self::token_funds(dao_id) };
+        if p.amount > funds {
+            proposal_check = false;
+        }
+        let (raw_yea, raw_nay) = Self::raw_tally(proposal_id);
+        let quorum = Self::quorum_of(proposal_id);
+
+        // Need improved
+        let min_quorum_divisor = dao.min_quorum_divisor;
+        if transaction_data.len() >= 4 && quorum < Self::min_quorum(dao_id, min_quorum_divisor, funds, pays_native)
+        {
+            proposal_check = false;
+        }
+
+        let quorum_met = quorum >= Self::min_quorum(dao_id, min_quorum_divisor, p.amount, pays_native);
+        if quorum_met {
+            let total_supply = Self::total_supply(dao_id);
+            <Daos<T>>::mutate(dao_id, |dao| {
+                if let Some(dao) = dao {
+                    dao.last_time_min_quorum_met = now;
+                    if quorum > total_supply / <BalanceOf<T>>::sa(7) {
+                        dao.min_quorum_divisor = 7;
+                    }
+                }
+            });
+        }
+
+        <Proposals<T>>::mutate(proposal_id, |p|{
+            if quorum_met && p.yea > p.nay && proposal_check {
+                p.proposal_passed = true;
+            }
+        });
+        Self::close_proposal(proposal_id)?;
+        Self::settle_deposit(proposal_id, quorum_met)?;
+
+        Self::deposit_event(RawEvent::ProposalTaillied(proposal_id, true, quorum, p.yea, p.nay, raw_yea, raw_nay));
+
+        if !Self::proposals(proposal_id).proposal_passed {
+            <ProposalCalls<T>>::remove(proposal_id);
+            <CouncilProposals<T>>::remove(proposal_id);
+            <ParameterProposals<T>>::remove(proposal_id);
+            if let Some(bounty_id) = Self::bounty_of_proposal(proposal_id) {
+                <Bounties<T>>::remove(bounty_id);
+                <BountyProposals<T>>::remove(proposal_id);
+            }
+            return Ok(());
+        }
+
+        Self::enact_or_wait_grace_period(proposal_id)
+    }
+
+    /// Pass an optimistic proposal which was not vetoed during its challenge window.
+    fn execute_optimistic(proposal_id: u64) -> Result {
+        let dao_id = Self::dao_of(proposal_id);
+        let p = Self::proposals(proposal_id);
+        ensure!(p.open, "Proposal not open");
+        <ChallengeEnds<T>>::remove(proposal_id);
+
+        let funds = if Self::pays_native(proposal_id) { Self::actual_balance(dao_id) } else { Self::token_funds(dao_id) };
+        let passed = Self::allowed_recipients((dao_id, p.recipient.clone())) && p.amount <= funds;
+        <Proposals<T>>::mutate(proposal_id, |p| p.proposal_passed = passed);
+        Self::close_proposal(proposal_id)?;
+        Self::settle_deposit(proposal_id, true)?;
+        ensure!(passed, "The proposal can not be paid out");
+
+        Self::enact_or_wait_grace_period(proposal_id)
+    }
+
+    /// A passed proposal stays in its grace period until it is enacted, so a failed
+    /// enactment can be retried with `execute_proposal`.
+    fn enact_or_wait_grace_period(proposal_id: u64) -> Result {
+        let dao = Self::dao_info(Self::dao_of(proposal_id))?;
+        let end = Self::now_for(proposal_id) + dao.grace_period;
+        <GracePeriodEnds<T>>::insert(proposal_id, end.clone());
+        if dao.grace_period > T::Moment::sa(0) {
+            // the members who did not vote yes can ragequit before it is executed
+            Self::schedule(proposal_id, end);
+            Ok(())
+        } else {
+            Self::try_enact(proposal_id)
+        }
+    }
+
+    fn try_enact(proposal_id: u64) -> Result {
+        let result = Self::enact_proposal(proposal_id);
+        if result.is_err() {
+            Self::deposit_event(RawEvent::EnactmentFailed(proposal_id));
+        }
+        result
+    }
+
+    /// Pay out and dispatch a passed proposal.
+    fn enact_proposal(proposal_id: u64) -> Result {
+        let dao_id = Self::dao_of(proposal_id);
+        let dao = Self::dao_info(dao_id)?;
+        let p = Self::proposals(proposal_id);
+
+        if Self::pays_native(proposal_id) {
+            T::Currency::transfer(&Self::treasury_account(dao_id), &p.recipient, p.amount)?;
+        } else {
+            Self::transfer_tokens(dao_id, Self::treasury_account(dao_id), p.recipient.clone(), p.amount)?;
+        }
+        <GracePeriodEnds<T>>::remove(proposal_id);
+
+        if let Some(call) = Self::proposal_call(proposal_id) {
+            let ok = call.dispatch(RawOrigin::Dao(dao_id).into()).is_ok();
+            Self::deposit_event(RawEvent::ProposalExecuted(proposal_id, ok));
+            <ProposalCalls<T>>::remove(proposal_id);
+        }
+
+        if let Some(bounty_id) = Self::bounty_of_proposal(proposal_id) {
+            <Bounties<T>>::mutate(bounty_id, |b| {
+                if let Some(b) = b {
+                    b.status = BountyStatus::Active;
+                }
+            });
+            <BountyProposals<T>>::remove(proposal_id);
+            Self::deposit_event(RawEvent::BountyFunded(bounty_id));
+        }
+
+        if let Some((council, threshold)) = Self::council_proposal(proposal_id) {
+            Self::set_council(dao_id, council, threshold);
+            <CouncilProposals<T>>::remove(proposal_id);
+        }
+
+        if <ParameterProposals<T>>::exists(proposal_id) {
+            let at = Self::now(dao_id) + dao.parameter_timelock;
+            <ParameterChangeTimes<T>>::insert(proposal_id, at.clone());
+            Self::schedule(proposal_id, at.clone());
+            Self::deposit_event(RawEvent::ParameterChangeScheduled(dao_id, proposal_id, at));
+        }
+
+        Ok(())
+    }
+
+    fn execute_scheduled(proposal_id: u64) {
+        let now = Self::now_for(proposal_id);
+        if let Some(end) = Self::challenge_end(proposal_id) {
+            if now >= end {
+                let _ = Self::execute_optimistic(proposal_id);
+            }
+            return;
+        }
+
+        if let Some(end) = Self::grace_period_end(proposal_id) {
+            if now >= end {
+                let _ = Self::try_enact(proposal_id);
+            }
+            return;
+        }
+
+        if let Some(at) = Self::parameter_change_at(proposal_id) {
+            if now >= at {
+                if Self::apply_parameter(proposal_id).is_err() {
+                    let dao_id = Self::dao_of(proposal_id);
+                    Self::deposit_event(RawEvent::ParameterChangeFailed(dao_id, proposal_id));
+                }
+            }
+            return;
+        }
+
+        // a vetoed optimistic proposal is still scheduled at the end of its challenge window
+        let p = Self::proposals(proposal_id);
+        if !p.open || now < p.voting_deadline {
+            return;
+        }
+
+        // a proposal that can not be executed is closed instead
+        let result = Self::execute_proposal_impl(proposal_id, Self::transaction_data(proposal_id));
+        if result.is_err() && Self::proposals(proposal_id).open {
+            let _ = Self::close_proposal(proposal_id);
+            // it was not tallied, the deposit is returned
+            let _ = Self::settle_deposit(proposal_id, true);
+        }
+    }
+
+    /// Bounds of a parameter change, they are checked again when the change takes effect.
+    fn check_parameter(dao_id: u64, parameter: &DaoParameterOf<T>) -> Result {
+        let dao = Self::dao_info(dao_id)?;
+        let zero = T::Moment::sa(0);
+        match parameter {
+            DaoParameter::MinProposalDeposit(v) => ensure!(
+                *v <= Self::token_funds(dao_id) / <BalanceOf<T>>::sa(dao.max_deposit_divisor),
+                "The deposit is more than the funds allow"
+            ),
+            DaoParameter::MinQuorumDivisor(d) => ensure!(*d > 0, "The divisors can not be zero"),
+            DaoParameter::MaxDepositDivisor(d)
+            | DaoParameter::VetoDivisor(d)
+            | DaoParameter::RewardDivisor(d) => {
+                ensure!(*d > 0, "The divisors can not be zero")
+            }
+            DaoParameter::MinProposalDebatePeriod(t) => ensure!(
+                *t < dao.max_proposal_debate_period,
+                "Invalid debate periods"
+            ),
+            DaoParameter::MaxProposalDebatePeriod(t) => ensure!(
+                *t > dao.min_proposal_debate_period,
+                "Invalid debate periods"
+            ),
+            DaoParameter::QuorumHavlingPeriod(t)
+            | DaoParameter::ExecuteProposalPeriod(t)
+            | DaoParameter::ChallengePeriod(t) => {
+                ensure!(*t > zero, "The period can not be zero")
+            }
+            DaoParameter::ParameterTimelock(_) | DaoParameter::RewardClaimPeriod(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Apply the parameter of a passed parameter proposal once its timelock has ended.
+    fn apply_parameter(proposal_id: u64) -> Result {
+        let parameter = Self::parameter_proposal(proposal_id).ok_or("Not a parameter proposal")?;
+        let dao_id = Self::dao_of(proposal_id);
+        <ParameterProposals<T>>::remove(proposal_id);
+        <ParameterChangeTimes<T>>::remove(proposal_id);
+        // the other parameters may have changed since the proposal was created
+        Self::check_parameter(dao_id, &parameter)?;
+
+        let mut dao = Self::dao_info(dao_id)?;
+        match parameter {
+            DaoParameter::MinProposalDeposit(v) => dao.min_proposal_deposit = v,
+            DaoParameter::MinQuorumDivisor(d) => dao.min_quorum_divisor = d,
+            DaoParameter::MinProposalDebatePeriod(t) => dao.min_proposal_debate_period = t,
+            DaoParameter::MaxProposalDebatePeriod(t) => dao.max_proposal_debate_period = t,
+            DaoParameter::QuorumHavlingPeriod(t) => dao.quorum_havling_period = t,
+            DaoParameter::ExecuteProposalPeriod(t) => dao.execute_proposal_period = t,
+            DaoParameter::MaxDepositDivisor(d) => dao.max_deposit_divisor = d,
+            DaoParameter::ParameterTimelock(t) => dao.parameter_timelock = t,
+            DaoParameter::ChallengePeriod(t) => dao.challenge_period = t,
+            DaoParameter::VetoDivisor(d) => dao.veto_divisor = d,
+            DaoParameter::RewardDivisor(d) => dao.reward_divisor = d,
+            DaoParameter::RewardClaimPeriod(t) => dao.reward_claim_period = t,
+        }
+        <Daos<T>>::insert(dao_id, dao);
+
+        Self::deposit_event(RawEvent::ParameterChanged(dao_id, proposal_id));
+        Ok(())
+    }
+
+    /// Yes votes counted for the quorum: the tokens behind the votes rather than their
+    /// weight, which the conviction or the quadratic credits scale.
+    fn quorum_of(proposal_id: u64) -> BalanceOf<T> {
+        Self::raw_tally(proposal_id).0
+    }
+
+    /// Return the deposit of a closed proposal to its creator. If the proposal missed the
+    /// quorum, `deposit_slash_percent` of it goes to the DAO treasury instead, where it adds
+    /// to the token funds paid out by the proposals.
+    fn settle_deposit(proposal_id: u64, quorum_met: bool) -> Result {
+        let dao_id = Self::dao_of(proposal_id);
+        let dao = Self::dao_info(dao_id)?;
+        let p = Self::proposals(proposal_id);
+
+        let slashed = if quorum_met {
+            <BalanceOf<T>>::sa(0)
+        } else {
+            p.proposal_deposit * <BalanceOf<T>>::sa(dao.deposit_slash_percent as u64) / <BalanceOf<T>>::sa(100)
+        };
+        if slashed > <BalanceOf<T>>::sa(0) {
+            Self::unlock_tokens(dao_id, Self::treasury_account(dao_id), slashed, proposal_id)?;
+            Self::deposit_event(RawEvent::DepositSlashed(proposal_id, p.creator.clone(), slashed));
+        }
+        Self::unlock_tokens(dao_id, p.creator, p.proposal_deposit - slashed, proposal_id)
+    }
+
+    /// Execute `proposal_id` in `on_finalize` once the clock of its DAO reaches `at`.
+    fn schedule(proposal_id: u64, at: T::Moment) {
+        Self::schedule_task(ScheduledTask::Proposal(proposal_id), at);
+    }
+
+    /// Run `task` in `on_finalize` once the clock of the DAO of its proposal reaches `at`. A
+    /// time the cursor of the clock has passed is moved to the cursor.
+    fn schedule_task(task: ScheduledTask, at: T::Moment) {
+        let proposal_id = match task {
+            ScheduledTask::Proposal(id) | ScheduledTask::ExpireRewards(id) => id,
+        };
+        let clock = Self::clock_of(Self::dao_of(proposal_id));
+        let at = match Self::schedule_cursor(clock) {
+            Some(cursor) => at.max(cursor),
+            None => {
+                <ScheduleCursors<T>>::insert(clock, at.clone().min(clock.now::<T>()));
+                at
+            }
+        };
+        <ScheduledTasks<T>>::mutate((clock, at), |tasks| tasks.push(task));
+    }
+
+    /// Move the tasks scheduled on `clock` until now to `due`, until it holds
+    /// `MAX_EXECUTIONS_PER_BLOCK` of them. At most `MAX_SCHEDULE_SCAN` times are checked.
+    fn take_due(clock: Clock, due: &mut Vec<ScheduledTask>) {
+        let mut cursor = match Self::schedule_cursor(clock) {
+            Some(cursor) => cursor,
+            None => return,
+        };
+        let now = clock.now::<T>();
+
+        let mut scanned = 0;
+        while cursor <= now && scanned < MAX_SCHEDULE_SCAN && due.len() < MAX_EXECUTIONS_PER_BLOCK {
+            let key = (clock, cursor.clone());
+            if <ScheduledTasks<T>>::exists(&key) {
+                let mut tasks = Self::scheduled_tasks(&key);
+                let count = tasks.len().min(MAX_EXECUTIONS_PER_BLOCK - due.len());
+                due.extend(tasks.drain(..count));
+                if !tasks.is_empty() {
+                    // the rest of this time is run in the next block
+                    <ScheduledTasks<T>>::insert(&key, tasks);
+                    break;
+                }
+                <ScheduledTasks<T>>::remove(&key);
+            }
+            cursor = cursor + T::Moment::sa(1);
+            scanned += 1;
+        }
+        <ScheduleCursors<T>>::insert(clock, cursor);
+    }
+
+    fn create_proposal(
+        dao_id: u64,
+        sender: T::AccountId,
+        recipient: T::AccountId,
+        amount: BalanceOf<T>,
+        description: Vec<u8>,
+        transaction_data: Vec<u8>,
+        debating_period: T::Moment,
+        deposit: BalanceOf<T>,
+        new_curator: bool,
+    ) -> Result {
+        let mut dao = Self::dao_info(dao_id)?;
+        ensure!(debating_period > dao.min_proposal_debate_period, "debating_period too short");
+        ensure!(debating_period < dao.max_proposal_debate_period, "debating_period too long");
+
+        ensure!(deposit > dao.min_proposal_deposit, "deposit should be more than min_deposit");
+
+        let now = Self::now(dao_id);
+        // to prevent curator from halving quorum before first proposal
+        if dao.proposal_count == 0 {
+            dao.last_time_min_quorum_met = now.clone();
+        }
+        let index = dao.proposal_count;
+        dao.proposal_count += 1;
+
+        // safe?
+        let proposal_id = Self::proposal_count();
+        <ProposalCount<T>>::put(proposal_id + 1);
+
+        let voting_deadline = now.checked_add(&debating_period).ok_or("Overflow when setting voting deadline.")?;
+
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&amount.encode());
+        buf.extend_from_slice(&proposal_id.encode());
+        buf.extend_from_slice(&recipient.encode());
+        buf.extend_from_slice(&transaction_data);
+        let proposal_hash = <T as system::Trait>::Hashing::hash(&buf[..]);
+
+        let p = Proposal{
+            recipient: recipient.clone(),
+            amount,
+            description: description.clone(),
+            voting_deadline,
+            open: true,
+            proposal_passed: false,
+            proposal_hash,
+            proposal_deposit: deposit,
+            new_curator,
+            yea: <BalanceOf<T>>::sa(0),
+            nay: <BalanceOf<T>>::sa(0),
+            creator: sender.clone(),
+        };
+
+        dao.sum_of_proposal_deposits = dao.sum_of_proposal_deposits
+            .checked_add(&deposit)
+            .ok_or("Overflow in calculating sumOfProposalDeposits.")?;
+        let execute_proposal_period = dao.execute_proposal_period.clone();
+        <Daos<T>>::insert(dao_id, dao);
+
+        <Proposals<T>>::insert(proposal_id, p);
+        <ProposalDao<T>>::insert(proposal_id, dao_id);
+        <DaoProposals<T>>::insert((dao_id, index), proposal_id);
+        <TransactionData<T>>::insert(proposal_id, transaction_data);
+        Self::lock_tokens(dao_id, sender, deposit, proposal_id)?;
+
+        // a new curator proposal stays open for splits, it is only closed after the split period
+        if new_curator {
+            Self::schedule(proposal_id, voting_deadline + execute_proposal_period + T::Moment::sa(1));
+        } else {
+            Self::schedule(proposal_id, voting_deadline);
+        }
+
+        Self::deposit_event(RawEvent::ProposalAdded(
+            proposal_id,
+            recipient,
+            amount,
+            description
+        ));
+
+        Ok(())
+    }
+
+    fn _unvote(sender: T::AccountId, proposal_id: u64) -> Result {
+        ensure!(
+            Self::now_for(proposal_id) < Self::proposals(proposal_id).voting_deadline,
+            "Already past voting deadling"
+        );
+        let weight = Self::vote_weight((proposal_id, sender.clone()));
+        let raw = Self::vote_credits((proposal_id, sender.clone()));
+        <Proposals<T>>::mutate(proposal_id, |p| {
+            if Self::vote_yes((proposal_id, sender.clone())) {
+                p.yea -= weight;
+                <RawTally<T>>::mutate(proposal_id, |t| t.0 -= raw);
+                <VoteYes<T>>::insert((proposal_id, sender.clone()), false);
+            }
+
+            if Self::vote_no((proposal_id, sender.clone())) {
+                p.nay -= weight;
+                <RawTally<T>>::mutate(proposal_id, |t| t.1 -= raw);
+                <VoteNo<T>>::insert((proposal_id, sender.clone()), false);
+            }
+        });
+        <VoteWeights<T>>::remove((proposal_id, sender.clone()));
+        <VoteCredits<T>>::remove((proposal_id, sender.clone()));
+        for delegator in Self::counted_delegators((proposal_id, sender.clone())) {
+            <DelegatedVotes<T>>::remove((proposal_id, delegator));
+        }
+        <CountedDelegators<T>>::remove((proposal_id, sender));
+
+        Ok(())
+    }
+
+    /// Replace the previous vote of `sender`, `raw` tokens back a vote of `weight`.
+    /// With `delegable` the tokens delegated to `sender` are added to both.
+    fn record_vote(
+        sender: T::AccountId,
+        proposal_id: u64,
+        supports_proposal: bool,
+        raw: BalanceOf<T>,
+        weight: BalanceOf<T>,
+        delegable: bool,
+    ) -> Result {
+        // an optimistic proposal closes before its voting deadline
+        ensure!(Self::proposals(proposal_id).open, "Proposal not open");
+        let dao_id = Self::dao_of(proposal_id);
+        let blocked = Self::blocked((dao_id, sender.clone()));
+        ensure!(blocked == 0
+               || Self::proposals(proposal_id).voting_deadline > Self::proposals(blocked).voting_deadline,
+               "This is blocked");
+        Self::_unvote(sender.clone(), proposal_id)?;
+
+        // a direct vote overrides the delegation of the sender
+        Self::release_delegated_vote(proposal_id, sender.clone());
+        let (raw, weight) = if delegable {
+            let delegated = Self::take_delegated_votes(sender.clone(), proposal_id);
+            (raw + delegated, weight + delegated)
+        } else {
+            (raw, weight)
+        };
+
+        <Proposals<T>>::mutate(proposal_id, |p| {
+            <Blocked<T>>::insert((dao_id, sender.clone()), proposal_id);
+            if supports_proposal {
+                p.yea += weight;
+                <RawTally<T>>::mutate(proposal_id, |t| t.0 += raw);
+                <VoteYes<T>>::insert((proposal_id, sender.clone()), true);
+            } else {
+                p.nay += weight;
+                <RawTally<T>>::mutate(proposal_id, |t| t.1 += raw);
+                <VoteNo<T>>::insert((proposal_id, sender.clone()), true);
+            }
+        });
+        <VoteWeights<T>>::insert((proposal_id, sender.clone()), weight);
+        <VoteCredits<T>>::insert((proposal_id, sender.clone()), raw);
+
+        let voting_register_count = Self::voting_register_count(sender.clone());
+        <VotingRegister<T>>::insert((sender.clone(), voting_register_count), proposal_id);
+        <VotingRegisterCount<T>>::insert(sender.clone(), voting_register_count + 1);
+
+        Self::deposit_event(RawEvent::Voted(proposal_id, supports_proposal, sender));
+
+        Ok(())
+    }
+
+    /// The delegate `who` votes for on proposals of `topic`.
+    fn effective_delegate(dao_id: u64, who: T::AccountId, topic: Option<u32>) -> Option<T::AccountId> {
+        topic
+            .and_then(|t| Self::delegate_of((dao_id, who.clone(), Some(t))))
+            .or_else(|| Self::delegate_of((dao_id, who, None)))
+    }
+
+    fn remove_delegation(dao_id: u64, who: T::AccountId, topic: Option<u32>) {
+        if let Some(old) = Self::delegate_of((dao_id, who.clone(), topic)) {
+            <Delegators<T>>::mutate((dao_id, old, topic), |d| d.retain(|a| *a != who));
+            <Delegations<T>>::remove((dao_id, who, topic));
+        }
+    }
+
+    /// Accounts delegating directly to `who` on proposals of `topic`.
+    fn delegators_for(dao_id: u64, who: T::AccountId, topic: Option<u32>) -> Vec<T::AccountId> {
+        let mut delegators = Self::delegators_of((dao_id, who.clone(), None));
+        if let Some(t) = topic {
+            // a topic delegation to someone else overrides the global one
+            delegators.retain(|a| Self::delegate_of((dao_id, a.clone(), Some(t))).is_none());
+            delegators.extend(Self::delegators_of((dao_id, who, Some(t))));
+        }
+        delegators
+    }
+
+    /// The (delegator, delegate) edges whose voting power flows to `who`, up to
+    /// `MAX_DELEGATION_DEPTH` hops and `MAX_DELEGATION_GRAPH` edges. With a `proposal_id`,
+    /// the accounts that voted on it directly are left out together with their own delegators.
+    pub fn delegation_graph(
+        dao_id: u64,
+        who: T::AccountId,
+        topic: Option<u32>,
+        proposal_id: Option<u64>,
+    ) -> Vec<(T::AccountId, T::AccountId)> {
+        let mut edges = Vec::new();
+        let mut visited = Vec::new();
+        visited.push(who.clone());
+        let mut level = Vec::new();
+        level.push(who);
+
+        for _ in 0..MAX_DELEGATION_DEPTH {
+            let mut next = Vec::new();
+            for delegate in level {
+                for delegator in Self::delegators_for(dao_id, delegate.clone(), topic) {
+                    if visited.contains(&delegator) {
+                        continue;
+                    }
+                    visited.push(delegator.clone());
+                    if let Some(id) = proposal_id {
+                        if Self::vote_yes((id, delegator.clone())) || Self::vote_no((id, delegator.clone())) {
+                            continue;
+                        }
+                    }
+                    edges.push((delegator.clone(), delegate.clone()));
+                    if edges.len() == MAX_DELEGATION_GRAPH {
+                        return edges;
+                    }
+                    next.push(delegator);
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            level = next;
+        }
+
+        edges
+    }
+
+    /// Own and delegated tokens `who` votes with on proposals of `topic`.
+    pub fn voting_power(dao_id: u64, who: T::AccountId, topic: Option<u32>) -> BalanceOf<T> {
+        Self::delegation_graph(dao_id, who.clone(), topic, None)
+            .into_iter()
+            .fold(Self::balance_of(dao_id, who), |power, (delegator, _)| {
+                power + Self::balance_of(dao_id, delegator)
+            })
+    }
+
+    /// Count the tokens delegated to `voter` in its vote, taking them from the votes
+    /// of delegates further down the chain which counted them before. They are locked
+    /// until the voting deadline, so they can not be counted again from another account.
+    fn take_delegated_votes(voter: T::AccountId, proposal_id: u64) -> BalanceOf<T> {
+        let dao_id = Self::dao_of(proposal_id);
+        let deadline = Self::proposals(proposal_id).voting_deadline;
+        let mut delegated = <BalanceOf<T>>::sa(0);
+        let mut counted = Vec::new();
+
+        for (delegator, _) in Self::delegation_graph(dao_id, voter.clone(), Self::proposal_topic(proposal_id), Some(proposal_id)) {
+            Self::release_delegated_vote(proposal_id, delegator.clone());
+            let balance = Self::balance_of(dao_id, delegator.clone());
+            Self::extend_conviction_lock(dao_id, delegator.clone(), balance, deadline.clone());
+            <DelegatedVotes<T>>::insert((proposal_id, delegator.clone()), (voter.clone(), balance));
+            <DelegatedProposals<T>>::mutate((dao_id, delegator.clone()), |ids| {
+                ids.retain(|id| Self::proposals(*id).open);
+                if !ids.contains(&proposal_id) {
+                    ids.push(proposal_id);
+                }
+            });
+            delegated += balance;
+            counted.push(delegator);
+        }
+        <CountedDelegators<T>>::insert((proposal_id, voter), counted);
+
+        delegated
+    }
+
+    /// Remove the tokens of `delegator` from the vote that counted them.
+    fn release_delegated_vote(proposal_id: u64, delegator: T::AccountId) {
+        if let Some((voter, amount)) = Self::delegated_vote((proposal_id, delegator.clone())) {
+            <Proposals<T>>::mutate(proposal_id, |p| {
+                if Self::vote_yes((proposal_id, voter.clone())) {
+                    p.yea -= amount;
+                    <RawTally<T>>::mutate(proposal_id, |t| t.0 -= amount);
+                } else if Self::vote_no((proposal_id, voter.clone())) {
+                    p.nay -= amount;
+                    <RawTally<T>>::mutate(proposal_id, |t| t.1 -= amount);
+                }
+            });
+            <VoteWeights<T>>::mutate((proposal_id, voter.clone()), |w| *w -= amount);
+            <VoteCredits<T>>::mutate((proposal_id, voter.clone()), |c| *c -= amount);
+            <CountedDelegators<T>>::mutate((proposal_id, voter), |d| d.retain(|a| *a != delegator));
+            <DelegatedVotes<T>>::remove((proposal_id, delegator));
+        }
+    }
+
+    /// Largest integer whose square is not more than `n`.
+    fn integer_sqrt(n: BalanceOf<T>) -> BalanceOf<T> {
+        let two = <BalanceOf<T>>::sa(2);
+        if n < two {
+            return n;
+        }
+        let mut x = n / two + <BalanceOf<T>>::sa(1);
+        let mut y = (x + n / x) / two;
+        while y < x {
+            x = y;
+            y = (x + n / x) / two;
+        }
+        x
+    }
+
+    /// Locks can only grow: the largest amount and the latest expiry are kept.
+    fn extend_conviction_lock(dao_id: u64, who: T::AccountId, amount: BalanceOf<T>, until: T::Moment) {
+        let (locked, locked_until) = match Self::conviction_lock((dao_id, who.clone())) {
+            Some((locked, locked_until)) => (locked.max(amount), locked_until.max(until)),
+            None => (amount, until),
+        };
+
+        <ConvictionLocks<T>>::insert((dao_id, who.clone()), (locked, locked_until));
+        Self::set_vote_lock(dao_id, who, locked);
+    }
+
+    fn close_proposal(proposal_id: u64) -> Result {
+        let mut p = Self::proposals(proposal_id).clone();
+        if p.open {
+            let dao_id = Self::dao_of(proposal_id);
+            let mut dao = Self::dao_info(dao_id)?;
+            dao.sum_of_proposal_deposits = dao.sum_of_proposal_deposits
+                .checked_sub(&p.proposal_deposit)
+                .ok_or("Underflow when setting sum_of_proposal_deposits.")?;
+            let reward_divisor = dao.reward_divisor;
+            let reward_claim_period = dao.reward_claim_period.clone();
+            <Daos<T>>::insert(dao_id, dao);
+            Self::forfeit_commits(proposal_id)?;
+            Self::reserve_rewards(dao_id, proposal_id, p.yea + p.nay, reward_divisor, reward_claim_period);
+        }
+        p.open = false;
+        <Proposals<T>>::insert(proposal_id, p);
+        <TransactionData<T>>::remove(proposal_id);
+        Ok(())
+    }
+
+    /// Reserve a share of the reward pool for the voters of a closing proposal.
+    fn reserve_rewards(dao_id: u64, proposal_id: u64, total_weight: BalanceOf<T>, divisor: u64, claim_period: T::Moment) {
+        let zero = <BalanceOf<T>>::sa(0);
+        if total_weight == zero || divisor == 0 {
+            return;
+        }
+        let reward_account = Self::reward_account(dao_id);
+        let (reserved_tokens, reserved_native) = Self::rewards_reserved(dao_id);
+        let tokens = Self::balance_of(dao_id, reward_account.clone())
+            .checked_sub(&reserved_tokens)
+            .unwrap_or_default() / <BalanceOf<T>>::sa(divisor);
+        let native = T::Currency::free_balance(&reward_account)
+            .checked_sub(&reserved_native)
+            .unwrap_or_default() / <BalanceOf<T>>::sa(divisor);
+        if tokens == zero && native == zero {
+            return;
+        }
+
+        let expiry = Self::now(dao_id) + claim_period;
+        <RewardsReserved<T>>::insert(dao_id, (reserved_tokens + tokens, reserved_native + native));
+        <ProposalRewards<T>>::insert(proposal_id, (tokens, native, total_weight, expiry.clone()));
+        <UnclaimedRewards<T>>::insert(proposal_id, (tokens, native));
+        // kept apart from the grace period and timelock of the proposal, which may end later
+        Self::schedule_task(ScheduledTask::ExpireRewards(proposal_id), expiry);
+        Self::deposit_event(RawEvent::RewardsReserved(proposal_id, tokens, native));
+    }
+
+    /// Return the unclaimed rewards of a proposal to the pool.
+    fn expire_rewards(proposal_id: u64) {
+        let dao_id = Self::dao_of(proposal_id);
+        let (tokens, native) = Self::unclaimed_rewards(proposal_id);
+        <RewardsReserved<T>>::mutate(dao_id, |r| {
+            r.0 -= tokens;
+            r.1 -= native;
+        });
+        <UnclaimedRewards<T>>::remove(proposal_id);
+        <ProposalRewards<T>>::remove(proposal_id);
+    }
+
+    /// Send the deposits of the secret votes which were not revealed to the DAO treasury,
+    /// they add to its token funds.
+    fn forfeit_commits(proposal_id: u64) -> Result {
+        let dao_id = Self::dao_of(proposal_id);
+        for voter in Self::committers(proposal_id) {
+            if let Some((_, deposit)) = Self::vote_commit((proposal_id, voter.clone())) {
+                <VoteCommits<T>>::remove((proposal_id, voter.clone()));
+                Self::unlock_tokens(dao_id, Self::treasury_account(dao_id), deposit, proposal_id)?;
+                Self::deposit_event(RawEvent::CommitForfeited(proposal_id, voter, deposit));
+            }
+        }
+        <Committers<T>>::remove(proposal_id);
+        Ok(())
+    }
+
+    /// The keyless account holding the funds of the DAO, its governance tokens and native
+    /// currency. Nobody has its key, only passed proposals, ragequits and splits move them.
+    pub fn treasury_account(dao_id: u64) -> T::AccountId {
+        Self::keyless_account(b"dao/treasury", dao_id)
+    }
+
+    /// The keyless account holding the reward pool of the DAO, see `fund_rewards`.
+    pub fn reward_account(dao_id: u64) -> T::AccountId {
+        Self::keyless_account(b"dao/rewards", dao_id)
+    }
+
+    /// Return the budget of a bounty to the treasury of its DAO and remove the bounty.
+    fn refund_bounty(bounty_id: u64, bounty: BountyOf<T>) -> Result {
+        T::Currency::transfer(&Self::bounty_account(bounty_id), &Self::treasury_account(bounty.dao_id), bounty.budget)?;
+        <Bounties<T>>::remove(bounty_id);
+
+        Self::deposit_event(RawEvent::BountyCancelled(bounty_id, bounty.budget));
+        Ok(())
+    }
+
+    /// The keyless account holding the budget of a bounty.
+    pub fn bounty_account(bounty_id: u64) -> T::AccountId {
+        Self::keyless_account(b"dao/bounty", bounty_id)
+    }
+
+    fn keyless_account(prefix: &[u8], id: u64) -> T::AccountId {
+        let mut buf = prefix.to_vec();
+        buf.extend_from_slice(&id.encode());
+        let hash = <T as system::Trait>::Hashing::hash(&buf[..]);
+        T::AccountId::decode(&mut hash.as_ref()).unwrap_or_default()
+    }
+
+    /// Native currency held by the DAO treasury.
+    pub fn actual_balance(dao_id: u64) -> BalanceOf<T> {
+        T::Currency::free_balance(&Self::treasury_account(dao_id))
+    }
+
+    /// Governance tokens held by the DAO treasury, the proposal deposits are locked apart.
+    fn token_funds(dao_id: u64) -> BalanceOf<T> {
+        Self::balance_of(dao_id, Self::treasury_account(dao_id))
+    }
+
+    /// Yes votes needed by a proposal paying `value`, it grows with the part of the funds
+    /// paid out: the native currency of the treasury if `native`, its token funds otherwise.
+    fn min_quorum(dao_id: u64, min_quorum_divisor: u64, value: BalanceOf<T>, native: bool) -> BalanceOf<T> {
+        let funds = if native { Self::actual_balance(dao_id) } else { Self::token_funds(dao_id) };
+        // empty funds only require the base quorum
+        let voting_supply = Self::voting_supply(dao_id);
+        if funds == <BalanceOf<T>>::sa(0) {
+            return voting_supply / <BalanceOf<T>>::sa(min_quorum_divisor.into());
+        }
+        voting_supply / <BalanceOf<T>>::sa(min_quorum_divisor.into())
+            + (value * voting_supply) / (<BalanceOf<T>>::sa(3) * funds)
+    }
+
+    fn get_or_modify_blocked(dao_id: u64, account: T::AccountId) -> bool {
+        let blocked = Self::blocked((dao_id, account.clone()));
+        if blocked == 0 {
+            return false;
+        }
+        let p = Self::proposals(blocked);
+        if !p.open {
+            <Blocked<T>>::insert((dao_id, account), 0);
+            return false;
+        } else {
+            return true;
+        }
+    }
+
+    /// The open proposal blocking `account` in the DAO, if any. Read-only version of
+    /// `get_or_modify_blocked`.
+    pub fn blocked_by(dao_id: u64, account: T::AccountId) -> Option<u64> {
+        let blocked = Self::blocked((dao_id, account));
+        if blocked != 0 && Self::proposals(blocked).open {
+            Some(blocked)
+        } else {
+            None
+        }
+    }
+
+    /// Ids of the proposals of the DAO, skipping the first `start` matches and returning at
+    /// most `limit` (capped to `MAX_PAGE_SIZE`) items. `open` filters on the proposal state.
+    pub fn proposals_page(dao_id: u64, open: Option<bool>, start: u64, limit: u64) -> Vec<u64> {
+        let count = Self::daos(dao_id).map_or(0, |dao| dao.proposal_count);
+        (0..count)
+            .map(|index| Self::dao_proposal((dao_id, index)))
+            .filter(|id| open.map_or(true, |o| Self::proposals(*id).open == o))
+            .skip(start as usize)
+            .take(limit.min(MAX_PAGE_SIZE) as usize)
+            .collect()
+    }
+
+    /// The proposal with its current tally, quorum requirement and time remaining.
+    pub fn proposal_info(
+        proposal_id: u64,
+    ) -> Option<ProposalInfo<T::AccountId, BalanceOf<T>, T::Moment, T::Hash>> {
+        if !<Proposals<T>>::exists(proposal_id) {
+            return None;
+        }
+        let dao_id = Self::dao_of(proposal_id);
+        let dao = Self::daos(dao_id)?;
+        let proposal = Self::proposals(proposal_id);
+        let (raw_yea, raw_nay) = Self::raw_tally(proposal_id);
+        let time_remaining = proposal
+            .voting_deadline
+            .clone()
+            .checked_sub(&Self::now(dao_id))
+            .unwrap_or_default();
+
+        Some(ProposalInfo {
+            id: proposal_id,
+            dao_id,
+            quorum_required: Self::min_quorum(dao_id, dao.min_quorum_divisor, proposal.amount, Self::pays_native(proposal_id)),
+            proposal,
+            voting_mode: Self::voting_mode(proposal_id),
+            raw_yea,
+            raw_nay,
+            time_remaining,
+            pays_native: Self::pays_native(proposal_id),
+            grace_period_end: Self::grace_period_end(proposal_id),
+        })
+    }
+
+    /// Proposals `account` voted on from index `start` of its voting register, at most
+    /// `limit` (capped to `MAX_PAGE_SIZE`) items. Each item is the proposal id, the current
+    /// vote (`None` if it was withdrawn) and its weight.
+    pub fn voter_history(
+        account: T::AccountId,
+        start: u64,
+        limit: u64,
+    ) -> Vec<(u64, Option<bool>, BalanceOf<T>)> {
+        let end = start
+            .saturating_add(limit.min(MAX_PAGE_SIZE))
+            .min(Self::voting_register_count(account.clone()));
+        (start..end)
+            .map(|i| {
+                let proposal_id = Self::voting_register((account.clone(), i));
+                let vote = if Self::vote_yes((proposal_id, account.clone())) {
+                    Some(true)
+                } else if Self::vote_no((proposal_id, account.clone())) {
+                    Some(false)
+                } else {
+                    None
+                };
+                (proposal_id, vote, Self::vote_weight((proposal_id, account.clone())))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::dao;
+    use primitives::{Blake2Hasher, H256};
+    use runtime_io::with_externalities;
+    use runtime_primitives::{
+        testing::{Digest, DigestItem, Header, UintAuthorityId},
+        traits::{BlakeTwo256, IdentityLookup, Lazy, OnFinalize, Verify},
+        BuildStorage,
+    };
+    use support::traits::{LockableCurrency, WithdrawReasons};
+    use support::{assert_noop, assert_ok, impl_outer_origin};
+
+    impl_outer_origin! {
+        pub enum Origin for Test {
+            dao
+        }
+    }
+
+    // Signs the erc721 minting vouchers as the account it wraps
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+    pub struct MockSignature(u64);
+    impl Verify for MockSignature {
+        type Signer = u64;
+        fn verify<L: Lazy<[u8]>>(&self, _msg: L, signer: &u64) -> bool {
+            self.0 == *signer
+        }
+    }
+
+    // For testing the module, we construct most of a mock runtime. This means
+    // first constructing a configuration type (`Test`) which `impl`s each of the
+    // configuration traits of modules we want to use.
+    #[derive(Clone, Eq, PartialEq)]
+    pub struct Test;
+    impl system::Trait for Test {
+        type Origin = Origin;
+        type Index = u64;
+        type BlockNumber = u64;
+        type Hash = H256;
+        type Hashing = BlakeTwo256;
+        type Digest = Digest;
+        type AccountId = u64;
+        type Lookup = IdentityLookup<u64>;
+        type Header = Header;
+        type Event = ();
+        type Log = DigestItem;
+    }
+    impl consensus::Trait for Test {
+        type Log = DigestItem;
+        type SessionKey = UintAuthorityId;
+        type InherentOfflineReport = ();
+    }
+    impl token::Trait for Test {
+        type Event = ();
+        type TokenBalance = u64;
+    }
+    impl lockabletoken::Trait for Test {
+        type Event = ();
+        type TokenBalance = u64;
+    }
+    impl timestamp::Trait for Test {
+        type Moment = u64;
+        type OnTimestampSet = ();
+    }
+    impl balances::Trait for Test {
+        type Balance = u64;
+        type OnFreeBalanceZero = ();
+        type OnNewAccount = ();
+        type Event = ();
+        type TransactionPayment = ();
+        type TransferPayment = ();
+        type DustRemoval = ();
+    }
+    impl erc721::Trait for Test {
+        type Event = ();
+        type Signature = MockSignature;
+    }
+    impl Trait for Test {
+        type Event = ();
+        type Origin = Origin;
+        type Proposal = Call<Test>;
+        type ExternalOrigin = EnsureDao;
+        type Currency = balances::Module<Self>;
+    }
+    type Dao = Module<Test>;
+    type Token = token::Module<Test>;
+    type LockableToken = lockabletoken::Module<Test>;
+    type Timestamp = timestamp::Module<Test>;
+    type System = system::Module<Test>;
+    type Balances = balances::Module<Test>;
+    type ERC721 = erc721::Module<Test>;
+
+    fn init() -> Result {
+        Dao::init(Origin::signed(1))
+    }
+
+    fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+        new_test_ext_with_grace_period(0)
+    }
+
+    // builds the genesis config store and sets mock values
+    fn new_test_ext_with_grace_period(grace_period: u64) -> runtime_io::TestExternalities<Blake2Hasher> {
+        let mut t = system::GenesisConfig::<Test>::default()
+            .build_storage()
+            .unwrap()
+            .0;
+        t.extend(
+            token::GenesisConfig::<Test> {
+                total_supply: 21000000,
+                name: "ABMatrix Token".as_bytes().into(),
+                symbol: "ABT".as_bytes().into(),
+                decimal: 18,
+            }
+            .build_storage()
+            .unwrap()
+            .0,
+        );
+        t.extend(
+            balances::GenesisConfig::<Test> {
+                balances: vec![(Dao::treasury_account(0), 21000000), (1, 1000)],
+                transaction_base_fee: 0,
+                transaction_byte_fee: 0,
+                existential_deposit: 0,
+                transfer_fee: 0,
+                creation_fee: 0,
+                vesting: vec![],
+            }
+            .build_storage()
+            .unwrap()
+            .0,
+        );
+        t.extend(
+            GenesisConfig::<Test> {
+                curator: 1,
+                min_proposal_deposit: 100,
+                min_quorum_divisor: 7,
+                min_proposal_debate_period: 14,
+                max_proposal_debate_period: 4838400,
+                quorum_havling_period: 175,
+                execute_proposal_period: 10,
+                max_deposit_divisor: 100,
+                deposit_slash_percent: 0,
+                vote_lock_period: 10,
+                grace_period,
+                parameter_timelock: 5,
+                challenge_period: 5,
+                veto_divisor: 10,
+                reward_divisor: 2,
+                reward_claim_period: 10,
+                clock: Clock::Timestamp,
+            }
+            .build_storage()
+            .unwrap()
+            .0,
+        );
+        t.into()
+    }
+
+    #[test]
     fn should_init() {
         with_externalities(&mut new_test_ext(), || {
             assert_ok!(init());
-            assert_eq!(Dao::curator().unwrap(), 1);
-            assert_eq!(Dao::last_time_min_quorum_met().unwrap(), 0);
-            assert_eq!(Dao::proposal_count(), 1);
-            assert_eq!(Dao::allowed_recipients(1), true);
-            assert_eq!(Dao::allowed_recipients(2), false);
-            assert_eq!(Token::total_supply(), 21000000);
-            assert_eq!(Token::balance_of(1), 21000000);
-            assert_eq!(Token::balance_of(2), 0);
+            let dao = Dao::daos(0).unwrap();
+            assert_eq!(dao.curator, 1);
+            assert_eq!(dao.token, None);
+            assert_eq!(dao.last_time_min_quorum_met, 0);
+            assert_eq!(Dao::dao_count(), 1);
+            assert_eq!(Dao::proposal_count(), 1);
+            assert_eq!(Dao::allowed_recipients((0, 1)), true);
+            assert_eq!(Dao::allowed_recipients((0, 2)), false);
+            assert_eq!(Token::total_supply(), 21000000);
+            assert_eq!(Token::balance_of(1), 21000000);
+            assert_eq!(Token::balance_of(2), 0);
+
+            assert_eq!(dao.min_proposal_deposit, 100);
+            assert_eq!(dao.min_quorum_divisor, 7);
+            assert_eq!(dao.min_proposal_debate_period, 14);
+            assert_eq!(dao.quorum_havling_period, 175);
+            assert_eq!(dao.execute_proposal_period, 10);
+            assert_eq!(dao.max_deposit_divisor, 100);
+            assert_eq!(dao.vote_lock_period, 10);
+        });
+    }
+
+    #[test]
+    fn should_fail_insufficient_balance() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_noop!(
+                Dao::new_proposal(
+                    Origin::signed(2),
+                    0,
+                    1,
+                    10,
+                    "description".as_bytes().into(),
+                    "transaction_data".as_bytes().into(),
+                    15,
+                    101
+                ),
+                "This account doesn't hold the token"
+            );
+        });
+    }
+
+    #[test]
+    fn should_fail_not_allowed_recipients() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_noop!(
+                Dao::new_proposal(
+                    Origin::signed(1),
+                    0,
+                    2,
+                    10,
+                    "description".as_bytes().into(),
+                    "transaction_data".as_bytes().into(),
+                    15,
+                    101
+                ),
+                "The recipient is not in whitelist"
+            );
+        });
+    }
+
+    #[test]
+    fn should_fail_short_debating_period() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_noop!(
+                Dao::new_proposal(
+                    Origin::signed(1),
+                    0,
+                    1,
+                    10,
+                    "description".as_bytes().into(),
+                    "transaction_data".as_bytes().into(),
+                    13,
+                    101
+                ),
+                "debating_period too short"
+            );
+        });
+    }
+
+    #[test]
+    fn should_fail_long_debating_period() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_noop!(
+                Dao::new_proposal(
+                    Origin::signed(1),
+                    0,
+                    1,
+                    10,
+                    "description".as_bytes().into(),
+                    "transaction_data".as_bytes().into(),
+                    8 * 7 * 24 * 3600 + 1,
+                    101
+                ),
+                "debating_period too long"
+            );
+        });
+    }
+
+    #[test]
+    fn should_fail_low_deposit() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_noop!(
+                Dao::new_proposal(
+                    Origin::signed(1),
+                    0,
+                    1,
+                    10,
+                    "description".as_bytes().into(),
+                    "transaction_data".as_bytes().into(),
+                    15,
+                    100
+                ),
+                "deposit should be more than min_deposit"
+            );
+        });
+    }
+
+    #[test]
+    fn should_pass_proposal() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Dao::new_proposal(
+                Origin::signed(1),
+                0,
+                1,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                15,
+                101
+            ));
+        });
+    }
+
+    #[test]
+    fn should_pass_vote() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Dao::new_proposal(
+                Origin::signed(1),
+                0,
+                1,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_ok!(Dao::vote(Origin::signed(1), 1, true, 1));
+            assert_eq!(Dao::proposals(1).yea, 21000000 - 101);
+            assert_eq!(Dao::vote_yes((1, 1)), true);
+            assert_eq!(Dao::blocked((0, 1)), 1);
+            assert_eq!(Dao::voting_register((1, 0)), 1);
+            assert_eq!(Dao::voting_register_count(1), 1);
+        });
+    }
+
+    #[test]
+    fn should_pass_change_allowed_recipients() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(1), 0, 2, true));
+            assert_eq!(Dao::allowed_recipients((0, 2)), true);
+        });
+    }
+
+    #[test]
+    fn should_pass_execute_proposal() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Token::transfer(Origin::signed(1), Dao::treasury_account(0), 1000));
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(1), 0, 2, true));
+            assert_ok!(Dao::new_proposal(
+                Origin::signed(1),
+                0,
+                2,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_ok!(Dao::vote(Origin::signed(1), 1, true, 1));
+            Timestamp::set_timestamp(16);
+            assert_ok!(Dao::execute_proposal(
+                Origin::signed(1),
+                1,
+                "transaction_data".as_bytes().into()
+            ));
+            assert_eq!(Dao::proposals(1).proposal_passed, true);
+            // the tokens are paid by the treasury
+            assert_eq!(Token::balance_of(2), 10);
+            assert_eq!(Token::balance_of(Dao::treasury_account(0)), 1000 - 10);
+            assert_eq!(Token::balance_of(1), 21000000 - 1000);
+            assert_eq!(Dao::proposals(1).open, false);
+        });
+    }
+
+    #[test]
+    fn should_pass_unblock_me() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Dao::new_proposal(
+                Origin::signed(1),
+                0,
+                1,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_ok!(Dao::vote(Origin::signed(1), 1, true, 1));
+            assert_ok!(Dao::unblock_me(Origin::signed(1), 0));
+        });
+    }
+
+    #[test]
+    fn should_fail_split_without_yes_vote() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Token::transfer(Origin::signed(1), 2, 1000));
+            assert_ok!(Dao::new_curator_proposal(
+                Origin::signed(2),
+                0,
+                3,
+                "description".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_ok!(Dao::vote(Origin::signed(2), 1, false, 1));
+            Timestamp::set_timestamp(16);
+            assert_noop!(Dao::split_dao(Origin::signed(2), 1), "Only yes voters can split");
+        });
+    }
+
+    #[test]
+    fn should_fail_execute_new_curator_proposal() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Dao::new_curator_proposal(
+                Origin::signed(1),
+                0,
+                3,
+                "description".as_bytes().into(),
+                15,
+                101
+            ));
+            Timestamp::set_timestamp(16);
+            assert_noop!(
+                Dao::execute_proposal(Origin::signed(1), 1, Vec::new()),
+                "A new curator proposal is executed by split_dao"
+            );
+        });
+    }
+
+    #[test]
+    fn should_pass_split_dao() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Token::transfer(Origin::signed(1), 2, 1000));
+            assert_ok!(Token::transfer(Origin::signed(1), Dao::treasury_account(0), 1000000));
+            assert_ok!(Dao::new_curator_proposal(
+                Origin::signed(2),
+                0,
+                3,
+                "description".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_ok!(Dao::vote(Origin::signed(2), 1, true, 1));
+            Timestamp::set_timestamp(16);
+            assert_ok!(Dao::split_dao(Origin::signed(2), 1));
+
+            // the deposit is returned, then 1000 of the 21000000 tokens leave with their
+            // share of the 1000000 tokens and 21000000 native units of the treasury
+            assert_eq!(Dao::proposals(1).open, false);
+            assert_eq!(Token::balance_of(2), 0);
+            assert_eq!(Token::total_supply(), 21000000 - 1000 - 47);
+            assert_eq!(Token::balance_of(Dao::treasury_account(0)), 1000000 - 47);
+            assert_eq!(Dao::actual_balance(0), 21000000 - 1000);
+
+            // the child DAO holds them, 2 is a member with child DAO tokens
+            let child = Dao::child_daos(1).unwrap();
+            assert_eq!(child.dao_id, 1);
+            assert_eq!(Dao::dao_count(), 2);
+            let child_dao = Dao::daos(1).unwrap();
+            assert_eq!(child_dao.curator, 3);
+            assert_eq!(child_dao.token, Some(CHILD_TOKEN_BASE | 1));
+            assert_eq!(Dao::balance_of(1, 2), 1000);
+            assert_eq!(Dao::total_supply(1), 1000 + 47);
+            assert_eq!(Dao::token_funds(1), 47);
+            assert_eq!(Dao::actual_balance(1), 1000);
+
+            assert_noop!(Dao::split_dao(Origin::signed(2), 1), "Only yes voters can split");
+            assert_ok!(Dao::new_proposal(
+                Origin::signed(2),
+                1,
+                3,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_eq!(Dao::dao_of(2), 1);
+        });
+    }
+
+    #[test]
+    fn should_pass_execute_call_proposal() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Token::transfer(Origin::signed(1), Dao::treasury_account(0), 1000));
+            let call = Call::<Test>::spend(2, 10, false);
+            assert_ok!(Dao::new_call_proposal(
+                Origin::signed(1),
+                0,
+                Box::new(call.clone()),
+                "description".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_eq!(Dao::proposal_call(1), Some(call.clone()));
+            assert_ok!(Dao::vote(Origin::signed(1), 1, true, 1));
+            Timestamp::set_timestamp(16);
+            assert_noop!(
+                Dao::execute_proposal(Origin::signed(1), 1, Call::<Test>::spend(2, 11, false).encode()),
+                "Not match the proposal hash"
+            );
+            assert_ok!(Dao::execute_proposal(Origin::signed(1), 1, call.encode()));
+            assert_eq!(Dao::proposals(1).proposal_passed, true);
+            assert_eq!(Dao::proposal_call(1), None);
+            assert_eq!(Token::balance_of(2), 10);
+            assert_eq!(Token::balance_of(Dao::treasury_account(0)), 1000 - 10);
+        });
+    }
+
+    #[test]
+    fn should_not_dispatch_rejected_call_proposal() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Token::transfer(Origin::signed(1), Dao::treasury_account(0), 1000));
+            let call = Call::<Test>::spend(2, 10, false);
+            assert_ok!(Dao::new_call_proposal(
+                Origin::signed(1),
+                0,
+                Box::new(call.clone()),
+                "description".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_ok!(Dao::vote(Origin::signed(1), 1, false, 1));
+            Timestamp::set_timestamp(16);
+            assert_ok!(Dao::execute_proposal(Origin::signed(1), 1, call.encode()));
+            assert_eq!(Dao::proposals(1).proposal_passed, false);
+            assert_eq!(Token::balance_of(2), 0);
+        });
+    }
+
+    #[test]
+    fn should_fail_call_with_signed_origin() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_noop!(
+                Dao::spend(Origin::signed(1), 2, 10, false),
+                "bad origin: expected to be a DAO origin"
+            );
+            assert_noop!(
+                Call::<Test>::set_allowed_recipient(2, true).dispatch(Origin::signed(1)),
+                "bad origin: expected to be a DAO origin"
+            );
+            assert_noop!(
+                Dao::set_min_proposal_deposit(Origin::signed(1), 200),
+                "bad origin: expected to be a DAO origin"
+            );
+        });
+    }
+
+    #[test]
+    fn should_change_whitelist_by_call_proposal() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            let call = Call::<Test>::set_allowed_recipient(2, true);
+            assert_ok!(Dao::new_call_proposal(
+                Origin::signed(1),
+                0,
+                Box::new(call.clone()),
+                "description".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_ok!(Dao::vote(Origin::signed(1), 1, true, 1));
+
+            Timestamp::set_timestamp(16);
+            Dao::on_finalize(1);
+            assert_eq!(Dao::proposals(1).proposal_passed, true);
+            assert_eq!(Dao::allowed_recipients((0, 2)), true);
+        });
+    }
+
+    #[test]
+    fn should_execute_proposal_on_finalize() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Token::transfer(Origin::signed(1), Dao::treasury_account(0), 1000));
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(1), 0, 2, true));
+            assert_ok!(Dao::new_proposal(
+                Origin::signed(1),
+                0,
+                2,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_eq!(Dao::scheduled_tasks((Clock::Timestamp, 15)), vec![ScheduledTask::Proposal(1)]);
+            assert_ok!(Dao::vote(Origin::signed(1), 1, true, 1));
+
+            Timestamp::set_timestamp(14);
+            Dao::on_finalize(1);
+            assert_eq!(Dao::proposals(1).open, true);
+
+            Timestamp::set_timestamp(16);
+            Dao::on_finalize(2);
+            assert_eq!(Dao::proposals(1).proposal_passed, true);
+            assert_eq!(Dao::proposals(1).open, false);
+            assert_eq!(Token::balance_of(2), 10);
+            assert_eq!(Dao::scheduled_tasks((Clock::Timestamp, 15)), Vec::<ScheduledTask>::new());
+            assert_eq!(Dao::schedule_cursor(Clock::Timestamp), Some(17));
+        });
+    }
+
+    #[test]
+    fn should_bound_executions_per_block() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            for _ in 0..MAX_EXECUTIONS_PER_BLOCK + 1 {
+                assert_ok!(Dao::new_proposal(
+                    Origin::signed(1),
+                    0,
+                    1,
+                    10,
+                    "description".as_bytes().into(),
+                    "transaction_data".as_bytes().into(),
+                    15,
+                    101
+                ));
+            }
+
+            Timestamp::set_timestamp(16);
+            Dao::on_finalize(1);
+            assert_eq!(Dao::scheduled_tasks((Clock::Timestamp, 15)).len(), 1);
+            assert_eq!(Dao::proposals(MAX_EXECUTIONS_PER_BLOCK as u64).open, false);
+            assert_eq!(Dao::proposals(MAX_EXECUTIONS_PER_BLOCK as u64 + 1).open, true);
+
+            Dao::on_finalize(2);
+            assert_eq!(Dao::scheduled_tasks((Clock::Timestamp, 15)).len(), 0);
+            assert_eq!(Dao::proposals(MAX_EXECUTIONS_PER_BLOCK as u64 + 1).open, false);
+        });
+    }
+
+    #[test]
+    fn should_bound_schedule_scan_per_block() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Dao::new_proposal(
+                Origin::signed(1),
+                0,
+                1,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                2 * MAX_SCHEDULE_SCAN as u64 + 15,
+                101
+            ));
+
+            // the clock jumped, each block checks the next times up to now
+            Timestamp::set_timestamp(1000);
+            Dao::on_finalize(1);
+            assert_eq!(Dao::schedule_cursor(Clock::Timestamp), Some(MAX_SCHEDULE_SCAN as u64));
+            Dao::on_finalize(2);
+            assert_eq!(Dao::proposals(1).open, true);
+            Dao::on_finalize(3);
+            assert_eq!(Dao::proposals(1).open, false);
+        });
+    }
+
+    #[test]
+    fn should_fail_execute_proposal_unsigned() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_noop!(
+                Dao::execute_proposal(Origin::ROOT, 1, Vec::new()),
+                "bad origin: expected to be a signed origin"
+            );
+        });
+    }
+
+    #[test]
+    fn should_scale_vote_weight_with_conviction() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Token::transfer(Origin::signed(1), 2, 1000));
+            assert_ok!(Dao::new_proposal(
+                Origin::signed(1),
+                0,
+                1,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_noop!(
+                Dao::vote(Origin::signed(2), 1, true, MAX_CONVICTION + 1),
+                "conviction too high"
+            );
+
+            assert_ok!(Dao::vote(Origin::signed(2), 1, true, 0));
+            assert_eq!(Dao::proposals(1).yea, 100);
+            assert_eq!(Dao::quorum_of(1), 1000);
+            assert_eq!(Dao::conviction_lock((0, 2)), None);
+
+            // voting again replaces the previous vote
+            assert_ok!(Dao::vote(Origin::signed(2), 1, true, 3));
+            assert_eq!(Dao::proposals(1).yea, 3000);
+            assert_eq!(Dao::vote_conviction((1, 2)), 3);
+            // the conviction does not count towards the quorum
+            assert_eq!(Dao::quorum_of(1), 1000);
+            assert_eq!(Dao::conviction_lock((0, 2)), Some((1000, 15 + 4 * 10)));
+        });
+    }
+
+    #[test]
+    fn should_lock_tokens_until_unlock() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Token::transfer(Origin::signed(1), 2, 1000));
+            assert_ok!(Dao::new_proposal(
+                Origin::signed(1),
+                0,
+                1,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_ok!(Dao::vote(Origin::signed(2), 1, true, 2));
+            assert_eq!(Token::locked_balance_of(2), 1000);
+            assert_noop!(
+                Token::transfer(Origin::signed(2), 3, 1),
+                "Not enough unlocked balance."
+            );
+
+            Timestamp::set_timestamp(34);
+            assert_noop!(Dao::unlock(Origin::signed(2), 0), "The lock has not expired yet");
+
+            Timestamp::set_timestamp(35);
+            assert_ok!(Dao::unlock(Origin::signed(2), 0));
+            assert_eq!(Token::locked_balance_of(2), 0);
+            assert_ok!(Token::transfer(Origin::signed(2), 3, 1000));
+            assert_noop!(Dao::unlock(Origin::signed(2), 0), "No tokens are locked");
+        });
+    }
+
+    #[test]
+    fn should_weight_quadratic_votes_by_square_root() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Token::transfer(Origin::signed(1), 2, 10000));
+            assert_ok!(Token::transfer(Origin::signed(1), 3, 100));
+            assert_ok!(Dao::new_quadratic_proposal(
+                Origin::signed(1),
+                0,
+                1,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_eq!(Dao::voting_mode(1), VotingMode::Quadratic);
+            assert_noop!(
+                Dao::vote(Origin::signed(2), 1, true, 1),
+                "This proposal is voted with vote_quadratic"
+            );
+            assert_noop!(Dao::vote_quadratic(Origin::signed(3), 1, true, 101), "Not enough credits");
+
+            assert_ok!(Dao::vote_quadratic(Origin::signed(2), 1, false, 10000));
+            assert_ok!(Dao::vote_quadratic(Origin::signed(3), 1, true, 99));
+            assert_eq!(Dao::proposals(1).nay, 100);
+            assert_eq!(Dao::proposals(1).yea, 9);
+            assert_eq!(Dao::raw_tally(1), (99, 10000));
+
+            // the credits are locked until the deadline
+            assert_eq!(Dao::conviction_lock((0, 2)), Some((10000, 15)));
+            assert_noop!(
+                Token::transfer(Origin::signed(2), 3, 1),
+                "Not enough unlocked balance."
+            );
+
+            // voting again replaces the spent credits
+            assert_ok!(Dao::vote_quadratic(Origin::signed(3), 1, true, 100));
+            assert_eq!(Dao::proposals(1).yea, 10);
+            assert_eq!(Dao::raw_tally(1), (100, 10000));
+
+            // the credits spent on an open proposal can not be spent on another one
+            assert_ok!(Dao::new_quadratic_proposal(
+                Origin::signed(1),
+                0,
+                1,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                30,
+                101
+            ));
+            assert_noop!(Dao::vote_quadratic(Origin::signed(3), 2, true, 1), "Not enough credits");
+            assert_eq!(Dao::quadratic_commitments((0, 3)), vec![(1, 100)]);
+
+            Timestamp::set_timestamp(15);
+            assert_ok!(Dao::vote_quadratic(Origin::signed(3), 2, true, 100));
+            assert_eq!(Dao::quadratic_commitments((0, 3)), vec![(2, 100)]);
+            assert_eq!(Dao::conviction_lock((0, 3)), Some((100, 30)));
+        });
+    }
+
+    #[test]
+    fn should_vote_with_delegated_tokens() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Token::transfer(Origin::signed(1), 2, 100));
+            assert_ok!(Token::transfer(Origin::signed(1), 3, 200));
+            assert_ok!(Token::transfer(Origin::signed(1), 4, 300));
+            assert_ok!(Dao::new_proposal(
+                Origin::signed(1),
+                0,
+                1,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                15,
+                101
+            ));
+
+            // 2 -> 3 -> 4
+            assert_ok!(Dao::delegate(Origin::signed(2), 0, 3, None));
+            assert_ok!(Dao::delegate(Origin::signed(3), 0, 4, None));
+            assert_noop!(Dao::delegate(Origin::signed(4), 0, 2, None), "Delegation would create a cycle");
+            assert_eq!(Dao::voting_power(0, 4, None), 600);
+            assert_eq!(Dao::delegation_graph(0, 4, None, None), vec![(3, 4), (2, 3)]);
+
+            assert_ok!(Dao::vote(Origin::signed(4), 1, true, 1));
+            assert_eq!(Dao::proposals(1).yea, 600);
+            // the delegated tokens are locked until the deadline
+            assert_eq!(Dao::conviction_lock((0, 2)), Some((100, 15)));
+            assert_noop!(
+                Token::transfer(Origin::signed(2), 5, 1),
+                "Not enough unlocked balance."
+            );
+
+            // a direct vote takes the tokens of the voter and its delegators back
+            assert_ok!(Dao::vote(Origin::signed(3), 1, false, 1));
+            assert_eq!(Dao::proposals(1).yea, 300);
+            assert_eq!(Dao::proposals(1).nay, 300);
+            assert_eq!(Dao::delegated_vote((1, 2)), Some((3, 100)));
+
+            assert_ok!(Dao::vote(Origin::signed(2), 1, true, 1));
+            assert_eq!(Dao::proposals(1).yea, 400);
+            assert_eq!(Dao::proposals(1).nay, 200);
+            assert_eq!(Dao::raw_tally(1), (400, 200));
+
+            // 3 delegates to 4 already
+            for who in 1..MAX_DELEGATORS as u64 {
+                assert_ok!(Dao::delegate(Origin::signed(100 + who), 0, 4, None));
+            }
+            assert_noop!(Dao::delegate(Origin::signed(200), 0, 4, None), "Too many delegators");
+        });
+    }
+
+    #[test]
+    fn should_prefer_topic_delegation() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Token::transfer(Origin::signed(1), 2, 100));
+            assert_ok!(Dao::new_proposal(
+                Origin::signed(1),
+                0,
+                1,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_noop!(Dao::set_proposal_topic(Origin::signed(2), 1, 7), "Only the creator can set the topic");
+            assert_ok!(Dao::set_proposal_topic(Origin::signed(1), 1, 7));
+
+            assert_ok!(Dao::delegate(Origin::signed(2), 0, 3, None));
+            assert_ok!(Dao::delegate(Origin::signed(2), 0, 4, Some(7)));
+            assert_eq!(Dao::voting_power(0, 3, Some(7)), 0);
+            assert_eq!(Dao::voting_power(0, 4, Some(7)), 100);
+            assert_eq!(Dao::voting_power(0, 3, None), 100);
+
+            assert_ok!(Dao::vote(Origin::signed(3), 1, true, 1));
+            assert_ok!(Dao::vote(Origin::signed(4), 1, false, 1));
+            assert_eq!(Dao::proposals(1).yea, 0);
+            assert_eq!(Dao::proposals(1).nay, 100);
+
+            assert_ok!(Dao::undelegate(Origin::signed(2), 0, Some(7)));
+            assert_noop!(Dao::undelegate(Origin::signed(2), 0, Some(7)), "No delegation for this topic");
+            assert_eq!(Dao::voting_power(0, 3, Some(7)), 100);
+        });
+    }
+
+    #[test]
+    fn should_run_dao_with_lockabletoken() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_noop!(
+                Dao::create_dao(Origin::signed(5), 7, 10, 7, 14, 4838400, 175, 10, 100, 0, 10, 0, 5, 5, 10, 2, 10, None, Clock::Timestamp),
+                "The token does not exist"
+            );
+            assert_ok!(LockableToken::create_token(5, 7, "Team".as_bytes().into(), "TM".as_bytes().into(), 1000, 0));
+            assert_ok!(Dao::create_dao(Origin::signed(5), 7, 10, 7, 14, 4838400, 175, 10, 100, 0, 10, 0, 5, 5, 10, 2, 10, None, Clock::Timestamp));
+            assert_eq!(Dao::dao_count(), 2);
+            assert_eq!(Dao::daos(1).unwrap().curator, 5);
+            assert_eq!(Dao::daos(1).unwrap().token, Some(7));
+            assert_eq!(Dao::allowed_recipients((1, 5)), true);
+            assert_eq!(Dao::allowed_recipients((0, 5)), false);
+
+            assert_ok!(LockableToken::transfer(Origin::signed(5), 7, 6, 300));
+            assert_ok!(LockableToken::transfer(Origin::signed(5), 7, Dao::treasury_account(1), 100));
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(5), 1, 6, true));
+            assert_noop!(
+                Dao::change_allowed_recipients(Origin::signed(1), 1, 6, true),
+                "Only the curator council can do this"
+            );
+            // the daotoken holders are not members of this DAO
+            assert_noop!(
+                Dao::new_proposal(
+                    Origin::signed(1),
+                    1,
+                    6,
+                    10,
+                    "description".as_bytes().into(),
+                    "transaction_data".as_bytes().into(),
+                    15,
+                    11
+                ),
+                "This account doesn't hold the token"
+            );
+            assert_ok!(Dao::new_proposal(
+                Origin::signed(6),
+                1,
+                6,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                15,
+                11
+            ));
+            assert_eq!(Dao::dao_of(1), 1);
+            assert_eq!(LockableToken::balance_of((7, 6)), 300 - 11);
+            assert_eq!(Dao::daos(1).unwrap().sum_of_proposal_deposits, 11);
+            assert_eq!(Dao::daos(0).unwrap().sum_of_proposal_deposits, 0);
+
+            assert_ok!(Dao::vote(Origin::signed(5), 1, true, 1));
+            assert_eq!(Dao::proposals(1).yea, 600);
+            assert_eq!(LockableToken::locked_balance_of(7, 5), 600);
+            assert_eq!(Token::locked_balance_of(5), 0);
+
+            Timestamp::set_timestamp(16);
+            assert_ok!(Dao::execute_proposal(
+                Origin::signed(6),
+                1,
+                "transaction_data".as_bytes().into()
+            ));
+            assert_eq!(Dao::proposals(1).proposal_passed, true);
+            assert_eq!(LockableToken::balance_of((7, 6)), 300 + 10);
+            assert_eq!(LockableToken::balance_of((7, Dao::treasury_account(1))), 100 - 10);
+            assert_eq!(LockableToken::balance_of((7, 5)), 600);
+        });
+    }
+
+    #[test]
+    fn should_keep_vote_lock_of_other_dao_on_same_token() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(LockableToken::create_token(5, 7, "Team".as_bytes().into(), "TM".as_bytes().into(), 1000, 0));
+            for vote_lock_period in vec![10, 0] {
+                assert_ok!(Dao::create_dao(
+                    Origin::signed(5), 7, 10, 7, 14, 4838400, 175, 10, 100, 0, vote_lock_period, 0, 5, 5, 10, 2, 10,
+                    None,
+                    Clock::Timestamp
+                ));
+            }
+            for dao_id in 1..3 {
+                assert_ok!(LockableToken::transfer(Origin::signed(5), 7, Dao::treasury_account(dao_id), 100));
+                assert_ok!(Dao::change_allowed_recipients(Origin::signed(5), dao_id, 6, true));
+                assert_ok!(Dao::new_proposal(
+                    Origin::signed(5),
+                    dao_id,
+                    6,
+                    10,
+                    "description".as_bytes().into(),
+                    "transaction_data".as_bytes().into(),
+                    15,
+                    11
+                ));
+            }
+            assert_ok!(Dao::vote(Origin::signed(5), 1, true, 6));
+            let (locked, _) = Dao::conviction_lock((1, 5)).unwrap();
+
+            // a short lock in a second DAO of the token does not release the first one
+            assert_ok!(Dao::vote(Origin::signed(5), 2, true, 1));
+            Timestamp::set_timestamp(16);
+            assert_ok!(Dao::unlock(Origin::signed(5), 2));
+            assert_eq!(LockableToken::vote_lock((7, 2, 5)), 0);
+            assert_eq!(LockableToken::locked_balance_of(7, 5), locked);
+            assert_noop!(LockableToken::transfer(Origin::signed(5), 7, 8, 1), "Not enough unlocked balance.");
+        });
+    }
+
+    #[test]
+    fn should_require_council_threshold() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_eq!(Dao::daos(0).unwrap().council, vec![1]);
+            assert_noop!(
+                Dao::change_allowed_recipients(Origin::signed(2), 0, 2, true),
+                "Only the curator council can do this"
+            );
+            assert_ok!(Token::transfer(Origin::signed(1), 2, 1000));
+            assert_noop!(
+                Dao::new_council_proposal(Origin::signed(1), 0, vec![2, 2], 1, "description".as_bytes().into(), 15, 101),
+                "Duplicate council member"
+            );
+            assert_noop!(
+                Dao::new_council_proposal(Origin::signed(1), 0, vec![2, 3, 4], 4, "description".as_bytes().into(), 15, 101),
+                "Invalid threshold"
+            );
+            assert_ok!(Dao::new_council_proposal(
+                Origin::signed(1),
+                0,
+                vec![2, 3, 4],
+                2,
+                "description".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_ok!(Dao::vote(Origin::signed(1), 1, true, 1));
+            Timestamp::set_timestamp(16);
+            assert_ok!(Dao::execute_proposal(Origin::signed(1), 1, (vec![2u64, 3, 4], 2u32).encode()));
+            assert_eq!(Dao::daos(0).unwrap().council, vec![2, 3, 4]);
+            assert_eq!(Dao::daos(0).unwrap().threshold, 2);
+            assert_eq!(Dao::daos(0).unwrap().curator, 2);
+            assert_eq!(Dao::council_proposal(1), None);
+
+            // the previous curator lost its rights
+            assert_noop!(
+                Dao::change_allowed_recipients(Origin::signed(1), 0, 5, true),
+                "Only the curator council can do this"
+            );
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(2), 0, 5, true));
+            assert_eq!(Dao::allowed_recipients((0, 5)), false);
+            assert_noop!(
+                Dao::change_allowed_recipients(Origin::signed(2), 0, 5, true),
+                "You already approved this action"
+            );
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(4), 0, 5, true));
+            assert_eq!(Dao::allowed_recipients((0, 5)), true);
+            assert_eq!(Dao::pending_curator_actions(0), vec![]);
+        });
+    }
+
+    #[test]
+    fn should_ragequit_during_grace_period() {
+        with_externalities(&mut new_test_ext_with_grace_period(5), || {
+            assert_ok!(init());
+            assert_ok!(Token::transfer(Origin::signed(1), 2, 1000));
+            assert_ok!(Token::transfer(Origin::signed(1), 3, 2000));
+            assert_ok!(Token::transfer(Origin::signed(1), Dao::treasury_account(0), 1000000));
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(1), 0, 4, true));
+            assert_ok!(Dao::new_proposal(
+                Origin::signed(1),
+                0,
+                4,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_ok!(Dao::vote(Origin::signed(1), 1, true, 1));
+            assert_ok!(Dao::vote(Origin::signed(2), 1, false, 1));
+            assert_noop!(Dao::ragequit(Origin::signed(3), 1), "The proposal is not in its grace period");
+
+            Timestamp::set_timestamp(16);
+            assert_ok!(Dao::execute_proposal(
+                Origin::signed(1),
+                1,
+                "transaction_data".as_bytes().into()
+            ));
+            assert_eq!(Dao::proposals(1).proposal_passed, true);
+            assert_eq!(Dao::grace_period_end(1), Some(21));
+            assert_eq!(Token::balance_of(4), 0);
+            assert_noop!(
+                Dao::execute_proposal(Origin::signed(1), 1, Vec::new()),
+                "The grace period has not ended yet"
+            );
 
-            assert_eq!(Dao::min_proposal_deposit().unwrap(), 100);
-            assert_eq!(Dao::min_quorum_divisor().unwrap(), 7);
-            assert_eq!(Dao::min_proposal_debate_period().unwrap(), 14);
-            assert_eq!(Dao::quorum_havling_period().unwrap(), 175);
-            assert_eq!(Dao::execute_proposal_period().unwrap(), 10);
-            assert_eq!(Dao::max_deposit_divisor().unwrap(), 100);
+            assert_noop!(Dao::ragequit(Origin::signed(1), 1), "Yes voters can not ragequit");
+            // the no vote of 2 locks its tokens until 25
+            assert_noop!(Dao::ragequit(Origin::signed(2), 1), "The tokens are locked by a conviction vote");
+            assert_ok!(Dao::ragequit(Origin::signed(3), 1));
+            // 1000000 * 2000 / 21000000
+            assert_eq!(Token::balance_of(3), 95);
+            assert_eq!(Balances::free_balance(3), 2000);
+            assert_eq!(Token::total_supply(), 21000000 - 2000);
+            assert_eq!(Token::balance_of(Dao::treasury_account(0)), 1000000 - 95);
+            assert_eq!(Token::balance_of(1), 21000000 - 3000 - 1000000);
+
+            Timestamp::set_timestamp(21);
+            assert_noop!(Dao::ragequit(Origin::signed(3), 1), "The grace period has ended");
+            Dao::on_finalize(1);
+            assert_eq!(Dao::grace_period_end(1), None);
+            assert_eq!(Token::balance_of(4), 10);
         });
     }
 
     #[test]
-    fn should_fail_insufficient_balance() {
-        with_externalities(&mut new_test_ext(), || {
+    fn should_refuse_ragequit_of_counted_delegator() {
+        with_externalities(&mut new_test_ext_with_grace_period(5), || {
             assert_ok!(init());
-            assert_noop!(
-                Dao::new_proposal(
-                    Origin::signed(2),
-                    1,
+            assert_ok!(Token::transfer(Origin::signed(1), 2, 1000));
+            assert_ok!(Token::transfer(Origin::signed(1), 5, 500));
+            assert_ok!(Token::transfer(Origin::signed(1), Dao::treasury_account(0), 1000000));
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(1), 0, 4, true));
+            for _ in 0..2 {
+                assert_ok!(Dao::new_proposal(
+                    Origin::signed(1),
+                    0,
+                    4,
                     10,
                     "description".as_bytes().into(),
                     "transaction_data".as_bytes().into(),
                     15,
                     101
-                ),
-                "This account doesn't hold the token"
-            );
+                ));
+            }
+            assert_ok!(Dao::delegate(Origin::signed(5), 0, 2, None));
+            assert_ok!(Dao::vote(Origin::signed(1), 1, true, 1));
+            assert_ok!(Dao::vote(Origin::signed(2), 2, false, 0));
+            assert_eq!(Dao::delegated_vote((2, 5)), Some((2, 500)));
+
+            Timestamp::set_timestamp(16);
+            assert_ok!(Dao::execute_proposal(
+                Origin::signed(1),
+                1,
+                "transaction_data".as_bytes().into()
+            ));
+            assert_eq!(Dao::grace_period_end(1), Some(21));
+            // the tokens of 5 are still counted in the tally of the open proposal 2
+            assert_noop!(Dao::ragequit(Origin::signed(5), 1), "The tokens are counted in an open vote");
+
+            assert_ok!(Dao::execute_proposal(
+                Origin::signed(1),
+                2,
+                "transaction_data".as_bytes().into()
+            ));
+            assert_eq!(Dao::proposals(2).open, false);
+            assert_ok!(Dao::ragequit(Origin::signed(5), 1));
+            // 1000000 * 500 / 21000000
+            assert_eq!(Token::balance_of(5), 23);
         });
     }
 
     #[test]
-    fn should_fail_not_allowed_recipients() {
+    fn should_query_proposals_and_voters() {
         with_externalities(&mut new_test_ext(), || {
             assert_ok!(init());
-            assert_noop!(
-                Dao::new_proposal(
+            assert_ok!(Token::transfer(Origin::signed(1), 2, 1000));
+            assert_ok!(Token::transfer(Origin::signed(1), Dao::treasury_account(0), 1000));
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(1), 0, 4, true));
+            for _ in 0..2 {
+                assert_ok!(Dao::new_proposal(
                     Origin::signed(1),
-                    2,
+                    0,
+                    4,
                     10,
                     "description".as_bytes().into(),
                     "transaction_data".as_bytes().into(),
                     15,
                     101
-                ),
-                "The recipient is not in whitelist"
-            );
+                ));
+            }
+            assert_ok!(Dao::vote(Origin::signed(2), 1, true, 1));
+
+            assert_eq!(Dao::proposals_page(0, None, 0, 10), vec![1, 2]);
+            assert_eq!(Dao::proposals_page(0, None, 1, 10), vec![2]);
+            assert_eq!(Dao::proposals_page(1, None, 0, 10), Vec::<u64>::new());
+
+            let info = Dao::proposal_info(1).unwrap();
+            assert_eq!(info.dao_id, 0);
+            assert_eq!(info.proposal.yea, 1000);
+            assert_eq!((info.raw_yea, info.raw_nay), (1000, 0));
+            assert_eq!(info.time_remaining, 15);
+            // the tokens are paid from the 1000 token funds: 21000000 / 7 + 10 * 21000000 / (3 * 1000)
+            assert_eq!(info.quorum_required, 3070000);
+            assert_eq!(info.pays_native, false);
+            assert_eq!(Dao::proposal_info(3), None);
+
+            assert_eq!(Dao::voter_history(2, 0, 10), vec![(1, Some(true), 1000)]);
+            assert_eq!(Dao::blocked_by(0, 2), Some(1));
+
+            Timestamp::set_timestamp(16);
+            assert_ok!(Dao::execute_proposal(
+                Origin::signed(1),
+                1,
+                "transaction_data".as_bytes().into()
+            ));
+            assert_eq!(Dao::proposal_info(2).unwrap().time_remaining, 0);
+            assert_eq!(Dao::proposals_page(0, Some(true), 0, 10), vec![2]);
+            assert_eq!(Dao::proposals_page(0, Some(false), 0, 10), vec![1]);
+            assert_eq!(Dao::blocked_by(0, 2), None);
         });
     }
 
     #[test]
-    fn should_fail_short_debating_period() {
+    fn should_pay_native_proposal_from_treasury() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(1), 0, 4, true));
+            assert_ok!(Dao::new_native_proposal(
+                Origin::signed(1),
+                0,
+                4,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_eq!(Dao::pays_native(1), true);
+            // 21000000 / 7 + 10 * 21000000 / (3 * 21000000)
+            assert_eq!(Dao::proposal_info(1).unwrap().quorum_required, 3000003);
+            assert_ok!(Dao::vote(Origin::signed(1), 1, true, 1));
+
+            Timestamp::set_timestamp(16);
+            assert_ok!(Dao::execute_proposal(
+                Origin::signed(1),
+                1,
+                "transaction_data".as_bytes().into()
+            ));
+            assert_eq!(Dao::proposals(1).proposal_passed, true);
+            assert_eq!(Balances::free_balance(4), 10);
+            assert_eq!(Dao::actual_balance(0), 21000000 - 10);
+            assert_eq!(Token::balance_of(4), 0);
+            assert_eq!(Token::balance_of(1), 21000000);
+        });
+    }
+
+    #[test]
+    fn should_retry_failed_enactment() {
         with_externalities(&mut new_test_ext(), || {
             assert_ok!(init());
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(1), 0, 4, true));
+            assert_ok!(Dao::new_native_proposal(
+                Origin::signed(1),
+                0,
+                4,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_ok!(Dao::vote(Origin::signed(1), 1, true, 1));
+            // the treasury holds the funds, but can not pay them out
+            Balances::set_lock(*b"testlock", &Dao::treasury_account(0), 21000000, u64::max_value(), WithdrawReasons::all());
+
+            Timestamp::set_timestamp(16);
+            Dao::on_finalize(1);
+            assert_eq!(Dao::proposals(1).proposal_passed, true);
+            assert_eq!(Dao::proposals(1).open, false);
+            assert_eq!(Dao::grace_period_end(1), Some(16));
+            assert_eq!(Balances::free_balance(4), 0);
+
+            Balances::remove_lock(*b"testlock", &Dao::treasury_account(0));
+            assert_ok!(Dao::execute_proposal(
+                Origin::signed(1),
+                1,
+                "transaction_data".as_bytes().into()
+            ));
+            assert_eq!(Dao::grace_period_end(1), None);
+            assert_eq!(Balances::free_balance(4), 10);
             assert_noop!(
-                Dao::new_proposal(
+                Dao::execute_proposal(Origin::signed(1), 1, "transaction_data".as_bytes().into()),
+                "Proposal not open"
+            );
+        });
+    }
+
+    #[test]
+    fn should_slash_deposit_without_quorum() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            <Daos<Test>>::mutate(0, |dao| dao.as_mut().unwrap().deposit_slash_percent = 50);
+            assert_ok!(Token::transfer(Origin::signed(1), 2, 1000));
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(1), 0, 4, true));
+            for _ in 0..2 {
+                assert_ok!(Dao::new_proposal(
                     Origin::signed(1),
-                    1,
+                    0,
+                    4,
                     10,
                     "description".as_bytes().into(),
                     "transaction_data".as_bytes().into(),
-                    13,
+                    15,
                     101
-                ),
-                "debating_period too short"
+                ));
+            }
+            assert_ok!(Dao::vote(Origin::signed(2), 1, true, 1));
+            assert_ok!(Dao::vote(Origin::signed(1), 2, false, 1));
+
+            Timestamp::set_timestamp(16);
+            // 1000 yes votes are below the quorum, half the deposit is slashed
+            assert_ok!(Dao::execute_proposal(
+                Origin::signed(1),
+                1,
+                "transaction_data".as_bytes().into()
+            ));
+            assert_eq!(Token::balance_of(Dao::treasury_account(0)), 50);
+            assert_eq!(Dao::token_funds(0), 50);
+            assert_eq!(Token::balance_of(1), 21000000 - 1000 - 101 - 50);
+            assert_eq!(Token::locked_deposits(1), 0);
+
+            // the recipient left the whitelist, the deposit is returned
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(1), 0, 4, false));
+            assert_eq!(
+                Dao::execute_proposal(Origin::signed(1), 2, "transaction_data".as_bytes().into()),
+                Err("No such recipient in the whitelist.")
             );
+            assert_eq!(Token::balance_of(1), 21000000 - 1000 - 50);
+            assert_eq!(Token::locked_deposits(2), 0);
         });
     }
 
     #[test]
-    fn should_fail_long_debating_period() {
+    fn should_change_parameter_after_timelock() {
         with_externalities(&mut new_test_ext(), || {
             assert_ok!(init());
+            assert_noop!(
+                Dao::new_parameter_proposal(
+                    Origin::signed(1),
+                    0,
+                    DaoParameter::MinProposalDebatePeriod(4838400),
+                    "description".as_bytes().into(),
+                    15,
+                    101
+                ),
+                "Invalid debate periods"
+            );
+            let parameter = DaoParameter::MaxProposalDebatePeriod(100);
+            assert_ok!(Dao::new_parameter_proposal(
+                Origin::signed(1),
+                0,
+                parameter.clone(),
+                "description".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_ok!(Dao::vote(Origin::signed(1), 1, true, 1));
+
+            Timestamp::set_timestamp(16);
+            assert_ok!(Dao::execute_proposal(Origin::signed(1), 1, parameter.encode()));
+            assert_eq!(Dao::proposals(1).proposal_passed, true);
+            assert_eq!(Dao::parameter_change_at(1), Some(21));
+            assert_eq!(Dao::daos(0).unwrap().max_proposal_debate_period, 4838400);
+
+            Timestamp::set_timestamp(21);
+            Dao::on_finalize(1);
+            assert_eq!(Dao::daos(0).unwrap().max_proposal_debate_period, 100);
+            assert_eq!(Dao::parameter_proposal(1), None);
             assert_noop!(
                 Dao::new_proposal(
                     Origin::signed(1),
+                    0,
                     1,
                     10,
                     "description".as_bytes().into(),
                     "transaction_data".as_bytes().into(),
-                    8 * 7 * 24 * 3600 + 1,
+                    100,
                     101
                 ),
                 "debating_period too long"
@@ -584,114 +4007,444 @@ mod tests {
     }
 
     #[test]
-    fn should_fail_low_deposit() {
+    fn should_count_only_revealed_secret_votes() {
         with_externalities(&mut new_test_ext(), || {
             assert_ok!(init());
+            assert_ok!(Token::transfer(Origin::signed(1), 2, 1000));
+            assert_ok!(Token::transfer(Origin::signed(1), 3, 2000));
+            assert_ok!(Token::transfer(Origin::signed(1), Dao::treasury_account(0), 1000));
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(1), 0, 4, true));
+            assert_ok!(Dao::new_secret_proposal(
+                Origin::signed(1),
+                0,
+                4,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                15,
+                5,
+                101
+            ));
+            assert_eq!(Dao::reveal_start(1), Some(15));
+            assert_noop!(Dao::vote(Origin::signed(1), 1, true, 1), "This proposal is voted with commit_vote");
+
+            let salt: Vec<u8> = "salt".as_bytes().into();
+            let commit = |supports: bool| BlakeTwo256::hash_of(&(1u64, supports, salt.clone()));
+            assert_ok!(Dao::commit_vote(Origin::signed(1), 1, commit(true)));
+            assert_ok!(Dao::commit_vote(Origin::signed(2), 1, commit(false)));
+            assert_ok!(Dao::commit_vote(Origin::signed(3), 1, commit(false)));
+            assert_eq!(Token::balance_of(3), 2000 - 100);
+            assert_eq!(Dao::proposals(1).nay, 0);
+            // 3 is blocked by a vote on a proposal ending later
+            assert_ok!(Dao::new_proposal(
+                Origin::signed(1),
+                0,
+                4,
+                10,
+                "description".as_bytes().into(),
+                "transaction_data".as_bytes().into(),
+                30,
+                101
+            ));
+            assert_ok!(Dao::vote(Origin::signed(3), 2, false, 1));
             assert_noop!(
-                Dao::new_proposal(
+                Dao::reveal_vote(Origin::signed(1), 1, true, salt.clone()),
+                "The reveal window has not started"
+            );
+
+            Timestamp::set_timestamp(15);
+            assert_noop!(
+                Dao::commit_vote(Origin::signed(2), 1, commit(true)),
+                "The commit period has ended"
+            );
+            assert_noop!(
+                Dao::reveal_vote(Origin::signed(2), 1, true, salt.clone()),
+                "The vote does not match the commitment"
+            );
+            assert_ok!(Dao::reveal_vote(Origin::signed(1), 1, true, salt.clone()));
+            assert_ok!(Dao::reveal_vote(Origin::signed(2), 1, false, salt.clone()));
+            assert_eq!(Dao::proposals(1).nay, 1000);
+            assert_eq!(Token::balance_of(2), 1000);
+            // a vote which can not be counted keeps its deposit committed
+            assert_noop!(Dao::reveal_vote(Origin::signed(3), 1, false, salt.clone()), "This is blocked");
+
+            Timestamp::set_timestamp(20);
+            assert_ok!(Dao::execute_proposal(
+                Origin::signed(1),
+                1,
+                "transaction_data".as_bytes().into()
+            ));
+            assert_eq!(Dao::proposals(1).proposal_passed, true);
+            // the commit of 3 was not revealed
+            assert_eq!(Token::balance_of(3), 2000 - 100);
+            assert_eq!(Token::balance_of(Dao::treasury_account(0)), 1000 + 100 - 10);
+            assert_eq!(Dao::token_funds(0), 1000 + 100 - 10);
+            assert_eq!(Dao::vote_commit((1, 3)), None);
+        });
+    }
+
+    #[test]
+    fn should_pass_optimistic_proposal_without_veto() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(Token::transfer(Origin::signed(1), 2, 1000));
+            assert_ok!(Token::transfer(Origin::signed(1), 3, 3000000));
+            assert_ok!(Token::transfer(Origin::signed(1), Dao::treasury_account(0), 1000));
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(1), 0, 4, true));
+            for _ in 0..2 {
+                assert_ok!(Dao::new_optimistic_proposal(
                     Origin::signed(1),
-                    1,
+                    0,
+                    4,
                     10,
                     "description".as_bytes().into(),
                     "transaction_data".as_bytes().into(),
                     15,
-                    100
-                ),
-                "deposit should be more than min_deposit"
-            );
+                    101
+                ));
+            }
+            assert_eq!(Dao::challenge_end(1), Some(5));
+
+            // 1000 tokens are below a tenth of the supply
+            assert_ok!(Dao::veto(Origin::signed(2), 1));
+            assert_noop!(Dao::veto(Origin::signed(2), 1), "You already vetoed this proposal");
+            assert_ok!(Dao::veto(Origin::signed(3), 2));
+            assert_eq!(Dao::challenge_end(2), None);
+
+            Timestamp::set_timestamp(5);
+            assert_noop!(Dao::veto(Origin::signed(3), 1), "The challenge period has ended");
+            Dao::on_finalize(1);
+            assert_eq!(Dao::proposals(1).proposal_passed, true);
+            assert_eq!(Token::balance_of(4), 10);
+            assert_eq!(Token::locked_deposits(1), 0);
+            // the vetoed proposal waits for its vote
+            assert_eq!(Dao::proposals(2).open, true);
+
+            assert_ok!(Dao::vote(Origin::signed(3), 2, false, 1));
+            Timestamp::set_timestamp(15);
+            Dao::on_finalize(2);
+            assert_eq!(Dao::proposals(2).open, false);
+            assert_eq!(Dao::proposals(2).proposal_passed, false);
+            assert_eq!(Token::balance_of(4), 10);
         });
     }
 
     #[test]
-    fn should_pass_proposal() {
+    fn should_reward_voters_from_pool() {
         with_externalities(&mut new_test_ext(), || {
             assert_ok!(init());
+            assert_ok!(Token::transfer(Origin::signed(1), 2, 1000));
+            assert_ok!(Token::transfer(Origin::signed(1), 3, 3000));
+            assert_ok!(Dao::fund_rewards(Origin::signed(1), 0, 1000, false));
+            assert_ok!(Dao::fund_rewards(Origin::signed(1), 0, 600, true));
+            assert_eq!(Token::balance_of(Dao::reward_account(0)), 1000);
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(1), 0, 4, true));
             assert_ok!(Dao::new_proposal(
                 Origin::signed(1),
-                1,
+                0,
+                4,
                 10,
                 "description".as_bytes().into(),
                 "transaction_data".as_bytes().into(),
                 15,
                 101
             ));
+            assert_ok!(Dao::vote(Origin::signed(2), 1, true, 1));
+            assert_ok!(Dao::vote(Origin::signed(3), 1, false, 1));
+            assert_noop!(Dao::claim_reward(Origin::signed(2), 1), "No rewards for this proposal");
+
+            Timestamp::set_timestamp(16);
+            assert_ok!(Dao::execute_proposal(
+                Origin::signed(1),
+                1,
+                "transaction_data".as_bytes().into()
+            ));
+            // half of the pool for a vote weight of 4000
+            assert_eq!(Dao::proposal_reward(1), Some((500, 300, 4000, 26)));
+            assert_noop!(Dao::claim_reward(Origin::signed(4), 1), "Only voters can claim a reward");
+            assert_ok!(Dao::claim_reward(Origin::signed(2), 1));
+            assert_noop!(Dao::claim_reward(Origin::signed(2), 1), "The reward was already claimed");
+            assert_eq!(Token::balance_of(2), 1000 + 125);
+            assert_eq!(Balances::free_balance(2), 75);
+            assert_eq!(Dao::rewards_reserved(0), (375, 225));
+
+            // the share of 3 rolls over to the pool
+            Timestamp::set_timestamp(26);
+            Dao::on_finalize(1);
+            assert_eq!(Dao::proposal_reward(1), None);
+            assert_eq!(Dao::rewards_reserved(0), (0, 0));
+            assert_noop!(Dao::claim_reward(Origin::signed(3), 1), "No rewards for this proposal");
         });
     }
 
     #[test]
-    fn should_pass_vote() {
-        with_externalities(&mut new_test_ext(), || {
+    fn should_expire_rewards_during_grace_period() {
+        with_externalities(&mut new_test_ext_with_grace_period(5), || {
             assert_ok!(init());
+            <Daos<Test>>::mutate(0, |dao| dao.as_mut().unwrap().reward_claim_period = 3);
+            assert_ok!(Token::transfer(Origin::signed(1), 2, 1000));
+            assert_ok!(Token::transfer(Origin::signed(1), Dao::treasury_account(0), 1000));
+            assert_ok!(Dao::fund_rewards(Origin::signed(1), 0, 1000, false));
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(1), 0, 4, true));
             assert_ok!(Dao::new_proposal(
                 Origin::signed(1),
-                1,
+                0,
+                4,
                 10,
                 "description".as_bytes().into(),
                 "transaction_data".as_bytes().into(),
                 15,
                 101
             ));
-            assert_ok!(Dao::vote(Origin::signed(1), 1, true));
-            assert_eq!(Dao::proposals(1).yea, 21000000 - 101);
-            assert_eq!(Dao::vote_yes(1), true);
-            assert_eq!(Dao::blocked(1), 1);
-            assert_eq!(Dao::voting_register((1, 0)), 1);
-            assert_eq!(Dao::voting_register_count(1), 1);
+            assert_ok!(Dao::vote(Origin::signed(1), 1, true, 1));
+            assert_ok!(Dao::vote(Origin::signed(2), 1, false, 1));
+
+            Timestamp::set_timestamp(16);
+            assert_ok!(Dao::execute_proposal(
+                Origin::signed(1),
+                1,
+                "transaction_data".as_bytes().into()
+            ));
+            assert_eq!(Dao::grace_period_end(1), Some(21));
+            assert_eq!(Dao::proposal_reward(1).map(|r| r.3), Some(19));
+            assert_eq!(Dao::rewards_reserved(0), (500, 0));
+
+            // the rewards expire before the grace period ends
+            Timestamp::set_timestamp(19);
+            Dao::on_finalize(1);
+            assert_eq!(Dao::proposal_reward(1), None);
+            assert_eq!(Dao::rewards_reserved(0), (0, 0));
+            assert_eq!(Dao::grace_period_end(1), Some(21));
+
+            Timestamp::set_timestamp(21);
+            Dao::on_finalize(2);
+            assert_eq!(Dao::grace_period_end(1), None);
+            assert_eq!(Token::balance_of(4), 10);
         });
     }
 
     #[test]
-    fn should_pass_change_allowed_recipients() {
+    fn should_pay_bounty_after_review_delay() {
         with_externalities(&mut new_test_ext(), || {
             assert_ok!(init());
-            assert_ok!(Dao::change_allowed_recipients(Origin::signed(1), 2, true));
-            assert_eq!(Dao::allowed_recipients(2), true);
+            for _ in 0..2 {
+                assert_ok!(Dao::new_bounty_proposal(
+                    Origin::signed(1),
+                    0,
+                    5,
+                    1000,
+                    5,
+                    "bounty".as_bytes().into(),
+                    15,
+                    101
+                ));
+            }
+            assert_eq!(Dao::bounties(0).unwrap().status, BountyStatus::Proposed);
+            assert_noop!(Dao::assign_bounty(Origin::signed(5), 0, 6), "The bounty is not funded yet");
+            assert_ok!(Dao::vote(Origin::signed(1), 1, true, 1));
+            assert_ok!(Dao::vote(Origin::signed(1), 2, true, 1));
+
+            Timestamp::set_timestamp(16);
+            assert_ok!(Dao::execute_proposal(Origin::signed(1), 1, 0u64.encode()));
+            assert_ok!(Dao::execute_proposal(Origin::signed(1), 2, 1u64.encode()));
+            assert_eq!(Dao::bounties(0).unwrap().status, BountyStatus::Active);
+            assert_eq!(Balances::free_balance(Dao::bounty_account(0)), 1000);
+            assert_eq!(Dao::actual_balance(0), 21000000 - 2000);
+
+            assert_noop!(Dao::assign_bounty(Origin::signed(6), 0, 6), "Only the bounty curator can do this");
+            assert_ok!(Dao::assign_bounty(Origin::signed(5), 0, 6));
+            assert_noop!(Dao::cancel_bounty(Origin::signed(5), 0), "Only an unassigned bounty can be cancelled");
+            assert_noop!(
+                Dao::submit_bounty(Origin::signed(7), 0, "work".as_bytes().into()),
+                "The bounty is not assigned to you"
+            );
+            assert_ok!(Dao::submit_bounty(Origin::signed(6), 0, "work".as_bytes().into()));
+            assert_noop!(Dao::assign_bounty(Origin::signed(5), 0, 7), "The work was already submitted");
+            assert_ok!(Dao::accept_bounty(Origin::signed(5), 0));
+            assert_noop!(Dao::assign_bounty(Origin::signed(5), 0, 7), "The work was already submitted");
+            assert_noop!(Dao::claim_bounty(Origin::signed(6), 0), "The review delay has not passed");
+
+            Timestamp::set_timestamp(21);
+            assert_ok!(Dao::claim_bounty(Origin::signed(6), 0));
+            assert_eq!(Balances::free_balance(6), 1000);
+            assert_eq!(Dao::bounties(0), None);
+
+            // the unassigned bounty returns its budget
+            assert_ok!(Dao::cancel_bounty(Origin::signed(5), 1));
+            assert_eq!(Dao::actual_balance(0), 21000000 - 1000);
+            assert_eq!(Dao::bounties(1), None);
         });
     }
 
     #[test]
-    fn should_pass_execute_proposal() {
+    fn should_close_bounty_by_call_proposal() {
         with_externalities(&mut new_test_ext(), || {
             assert_ok!(init());
-            assert_ok!(Dao::change_allowed_recipients(Origin::signed(1), 2, true));
-            assert_ok!(Dao::new_proposal(
+            assert_ok!(Dao::new_bounty_proposal(
                 Origin::signed(1),
-                2,
+                0,
+                5,
+                1000,
+                5,
+                "bounty".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_noop!(Dao::close_bounty(RawOrigin::Dao(0).into(), 0), "The bounty is not funded yet");
+            assert_ok!(Dao::vote(Origin::signed(1), 1, true, 1));
+            Timestamp::set_timestamp(16);
+            assert_ok!(Dao::execute_proposal(Origin::signed(1), 1, 0u64.encode()));
+            assert_ok!(Dao::assign_bounty(Origin::signed(5), 0, 6));
+            assert_ok!(Dao::submit_bounty(Origin::signed(6), 0, "work".as_bytes().into()));
+
+            assert_noop!(Dao::set_bounty_curator(Origin::signed(5), 0, 7), "bad origin: expected to be a DAO origin");
+            assert_noop!(
+                Dao::set_bounty_curator(RawOrigin::Dao(1).into(), 0, 7),
+                "The bounty belongs to another DAO"
+            );
+            assert_ok!(Dao::set_bounty_curator(RawOrigin::Dao(0).into(), 0, 7));
+            assert_noop!(Dao::accept_bounty(Origin::signed(5), 0), "Only the bounty curator can do this");
+
+            // the DAO cancels the bounty after the work was submitted
+            let call = Call::<Test>::close_bounty(0);
+            assert_ok!(Dao::new_call_proposal(
+                Origin::signed(1),
+                0,
+                Box::new(call),
+                "description".as_bytes().into(),
+                15,
+                101
+            ));
+            assert_ok!(Dao::vote(Origin::signed(1), 2, true, 1));
+            Timestamp::set_timestamp(32);
+            Dao::on_finalize(1);
+            assert_eq!(Dao::proposals(2).proposal_passed, true);
+            assert_eq!(Dao::bounties(0), None);
+            assert_eq!(Dao::actual_balance(0), 21000000);
+            assert_eq!(Balances::free_balance(Dao::bounty_account(0)), 0);
+        });
+    }
+
+    #[test]
+    fn should_vote_with_membership_nfts() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(init());
+            assert_ok!(LockableToken::create_token(5, 7, "Team".as_bytes().into(), "TM".as_bytes().into(), 1000, 0));
+            assert_ok!(Dao::create_dao(
+                Origin::signed(5), 7, 10, 7, 14, 4838400, 175, 10, 100, 0, 10, 0, 5, 5, 10, 2, 10,
+                Some(NftMembership::PerToken(9)),
+                Clock::Timestamp
+            ));
+            assert_ok!(LockableToken::transfer(Origin::signed(5), 7, Dao::treasury_account(1), 100));
+            for _ in 0..3 {
+                assert_ok!(ERC721::create_token(Origin::signed(9)));
+            }
+            let issued = ERC721::tokens_of_owner(9, 0, 3);
+            assert_ok!(ERC721::transfer_from(Origin::signed(9), 9, 2, issued[0]));
+            assert_ok!(ERC721::transfer_from(Origin::signed(9), 9, 2, issued[1]));
+            assert_ok!(ERC721::transfer_from(Origin::signed(9), 9, 3, issued[2]));
+            // a token issued outside the collection of the DAO does not vote
+            assert_ok!(ERC721::create_token(Origin::signed(2)));
+            let own_token = ERC721::token_of_owner_by_index((2, 2));
+            assert_eq!(Dao::voting_balance(1, 2), 2);
+            assert_eq!(Dao::voting_supply(1), 3);
+
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(5), 1, 6, true));
+            assert_ok!(Dao::new_proposal(
+                Origin::signed(5),
+                1,
+                6,
                 10,
                 "description".as_bytes().into(),
                 "transaction_data".as_bytes().into(),
                 15,
-                101
+                11
             ));
-            assert_ok!(Dao::vote(Origin::signed(1), 1, true));
+            assert_noop!(Dao::vote(Origin::signed(2), 1, true, 1), "NFT votes have no conviction");
+            assert_noop!(Dao::vote(Origin::signed(5), 1, true, 0), "This account doesn't hold a membership NFT");
+            assert_noop!(Dao::delegate(Origin::signed(3), 1, 2, None), "NFT votes can not be delegated");
+            assert_ok!(Dao::vote(Origin::signed(2), 1, true, 0));
+            assert_eq!(Dao::proposals(1).yea, 2);
+            assert_eq!(LockableToken::locked_balance_of(7, 2), 0);
+
+            // the NFTs can not vote again from another account, nor leave the supply
+            let token_id = ERC721::token_of_owner_by_index((2, 0));
+            assert_noop!(
+                ERC721::transfer_from(Origin::signed(2), 2, 4, token_id),
+                "The tokens of 'from' are locked by a vote"
+            );
+            assert_noop!(ERC721::burn(Origin::signed(2), token_id), "The tokens of the owner are locked by a vote");
+            // the tokens of the other collections stay free
+            assert_ok!(ERC721::transfer_from(Origin::signed(2), 2, 3, own_token));
+            assert_ok!(ERC721::transfer_from(Origin::signed(3), 3, 2, own_token));
+
             Timestamp::set_timestamp(16);
             assert_ok!(Dao::execute_proposal(
+                Origin::signed(6),
                 1,
                 "transaction_data".as_bytes().into()
             ));
             assert_eq!(Dao::proposals(1).proposal_passed, true);
-            assert_eq!(Token::balance_of(2), 10);
-            assert_eq!(Token::balance_of(1), 21000000 - 10);
-            assert_eq!(Dao::proposals(1).open, false);
+            assert_eq!(LockableToken::balance_of((7, 6)), 10);
+
+            assert_ok!(Dao::unlock(Origin::signed(2), 1));
+            assert_ok!(ERC721::transfer_from(Origin::signed(2), 2, 4, token_id));
+
+            // a DAO weighting the NFTs by their tier
+            assert_noop!(
+                ERC721::set_attribute(Origin::signed(2), token_id, "tier".as_bytes().into(), 5),
+                "Only the owner can set attributes"
+            );
+            assert_ok!(ERC721::set_attribute(Origin::signed(0), token_id, "tier".as_bytes().into(), 5));
+            assert_ok!(ERC721::set_attribute(Origin::signed(0), own_token, "tier".as_bytes().into(), 7));
+            assert_ok!(Dao::create_dao(
+                Origin::signed(5), 7, 10, 7, 14, 4838400, 175, 10, 100, 0, 10, 0, 5, 5, 10, 2, 10,
+                Some(NftMembership::Attribute(9, "tier".as_bytes().into())),
+                Clock::Timestamp
+            ));
+            assert_eq!(Dao::voting_balance(2, 4), 5);
+            assert_eq!(Dao::voting_balance(2, 2), 0);
+            assert_eq!(Dao::voting_supply(2), 5);
+            assert_eq!(ERC721::attribute_total("tier".as_bytes().to_vec()), 12);
         });
     }
 
     #[test]
-    fn should_pass_unblock_me() {
+    fn should_run_dao_on_block_numbers() {
         with_externalities(&mut new_test_ext(), || {
             assert_ok!(init());
+            System::set_block_number(1);
+            assert_ok!(LockableToken::create_token(5, 7, "Team".as_bytes().into(), "TM".as_bytes().into(), 1000, 0));
+            assert_ok!(Dao::create_dao(
+                Origin::signed(5), 7, 10, 7, 14, 4838400, 175, 10, 100, 0, 10, 0, 5, 5, 10, 2, 10,
+                None,
+                Clock::BlockNumber
+            ));
+            assert_ok!(LockableToken::transfer(Origin::signed(5), 7, Dao::treasury_account(1), 100));
+            assert_ok!(Dao::change_allowed_recipients(Origin::signed(5), 1, 6, true));
             assert_ok!(Dao::new_proposal(
-                Origin::signed(1),
+                Origin::signed(5),
                 1,
+                6,
                 10,
                 "description".as_bytes().into(),
                 "transaction_data".as_bytes().into(),
                 15,
-                101
+                11
             ));
-            assert_ok!(Dao::vote(Origin::signed(1), 1, true));
-            assert_ok!(Dao::unblock_me(Origin::signed(1)));
+            assert_eq!(Dao::proposals(1).voting_deadline, 16);
+            assert_eq!(Dao::scheduled_tasks((Clock::BlockNumber, 16)), vec![ScheduledTask::Proposal(1)]);
+            assert_ok!(Dao::vote(Origin::signed(5), 1, true, 1));
+
+            // the timestamp does not move the deadlines of this DAO
+            Timestamp::set_timestamp(100);
+            Dao::on_finalize(1);
+            assert_eq!(Dao::proposals(1).open, true);
+
+            System::set_block_number(16);
+            Dao::on_finalize(16);
+            assert_eq!(Dao::proposals(1).proposal_passed, true);
+            assert_eq!(LockableToken::balance_of((7, 6)), 10);
         });
     }
-
 }
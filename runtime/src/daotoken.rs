@@ -37,6 +37,8 @@ decl_storage! {
 
         // special interface
         LockedDeposits get(locked_deposits): map u64 => T::TokenBalance;
+        // Tokens locked by conviction votes in the DAO, they can not be transferred
+        LockedBalances get(locked_balance_of): map T::AccountId => T::TokenBalance;
     }
 }
 
@@ -50,6 +52,7 @@ decl_module! {
         /// Transfers token from the sender to the `to` address.
         fn transfer(origin, to: T::AccountId, #[compact] value: T::TokenBalance) -> Result {
             let sender = ensure_signed(origin)?;
+            Self::ensure_unlocked(sender.clone(), value)?;
             Self::transfer_impl(sender, to, value)
         }
 
@@ -82,6 +85,7 @@ decl_module! {
 
             Self::deposit_event(RawEvent::Approval(from.clone(), caller.clone(), value));
 
+            Self::ensure_unlocked(from.clone(), value)?;
             Self::transfer_impl(from, to, value)
         }
     }
@@ -110,8 +114,27 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    /// Check that `value` tokens of `from` are not locked by a vote.
+    fn ensure_unlocked(from: T::AccountId, value: T::TokenBalance) -> Result {
+        let unlocked = Self::balance_of(from.clone())
+            .checked_sub(&Self::locked_balance_of(from))
+            .unwrap_or_default();
+        ensure!(unlocked >= value, "Not enough unlocked balance.");
+
+        Ok(())
+    }
+
+    /// Lock `value` tokens of `who` against transfers, replacing the previous lock.
+    pub fn set_vote_lock(who: T::AccountId, value: T::TokenBalance) {
+        <LockedBalances<T>>::insert(who, value);
+    }
+
+    pub fn remove_vote_lock(who: T::AccountId) {
+        <LockedBalances<T>>::remove(who);
+    }
+
     /// internal transfer function
-    fn transfer_impl(from: T::AccountId, to: T::AccountId, value: T::TokenBalance) -> Result {
+    pub fn transfer_impl(from: T::AccountId, to: T::AccountId, value: T::TokenBalance) -> Result {
         ensure!(
             <Balances<T>>::exists(from.clone()),
             "Account does not own this token"
@@ -135,6 +158,24 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    /// Destroy `value` tokens of `from`, reducing the total supply.
+    pub fn burn(from: T::AccountId, value: T::TokenBalance) -> Result {
+        let balance_from = Self::balance_of(from.clone());
+        ensure!(balance_from >= value, "Not enough balance.");
+
+        let updated_balance_from = balance_from
+            .checked_sub(&value)
+            .ok_or("underflow in subtracting balance")?;
+        let updated_total_supply = Self::total_supply()
+            .checked_sub(&value)
+            .ok_or("underflow in subtracting total supply")?;
+
+        <Balances<T>>::insert(from, updated_balance_from);
+        <Totalsupply<T>>::put(updated_total_supply);
+
+        Ok(())
+    }
+
     pub fn lock(from: T::AccountId, value: T::TokenBalance, proposal_id: u64) -> Result {
         ensure!(
             <Balances<T>>::exists(from.clone()),
@@ -0,0 +1,239 @@
+use parity_codec::{Codec, Encode};
+/// A simple implementation of the ERC1155 multi token standard, not include ERC165
+use rstd::prelude::Vec;
+use runtime_primitives::traits::{As, CheckedAdd, CheckedSub, Hash, Member, SimpleArithmetic, Zero};
+use support::{
+    decl_event, decl_module, decl_storage, dispatch::Result, ensure, Parameter, StorageMap,
+    StorageValue,
+};
+use system::ensure_signed;
+
+/// Maximum number of token types moved by one batch transfer.
+const MAX_BATCH_SIZE: usize = 64;
+
+/// The module's configuration trait.
+pub trait Trait: balances::Trait {
+    type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+    type TokenBalance: Parameter
+        + Member
+        + SimpleArithmetic
+        + Codec
+        + Default
+        + Copy
+        + As<usize>
+        + As<u64>;
+}
+
+// This module's storage items.
+decl_storage! {
+    trait Store for Module<T: Trait> as ERC1155 {
+        // (token_id, account) => balance
+        Balances get(balance_of): map (T::Hash, T::AccountId) => T::TokenBalance;
+        OperatorApprovals get(is_approved_for_all): map (T::AccountId, T::AccountId) => bool;
+
+        // Optional metadata
+        Name get(name) config(): Vec<u8>;
+        Symbol get(symbol) config(): Vec<u8>;
+        Uris get(uri): map T::Hash => Vec<u8>;
+
+        // Not a part of the ERC1155 specification, but used to manage token types
+        Creators get(creator_of): map T::Hash => Option<T::AccountId>;
+        TotalSupply get(total_supply): map T::Hash => T::TokenBalance;
+
+        // Not a part of the ERC1155 specification, but used in random token generation
+        Nonce: u64;
+    }
+}
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        // Initializing events
+        // this is needed only if you are using events in your module
+        fn deposit_event<T>() = default;
+
+        /// Transfers `value` amount of `token_id` from `from` to `to`.
+        /// The sender must be `from` or an approved operator of `from`.
+        fn safe_transfer_from(origin, from: T::AccountId, to: T::AccountId, token_id: T::Hash, #[compact] value: T::TokenBalance) -> Result {
+            let operator = ensure_signed(origin)?;
+            ensure!(Self::is_approved(operator.clone(), from.clone()), "You can not transfer these tokens");
+            Self::ensure_existing_account(&to)?;
+
+            Self::transfer_impl(from.clone(), to.clone(), token_id, value)?;
+
+            Self::deposit_event(RawEvent::TransferSingle(operator, Some(from), Some(to), token_id, value));
+
+            Ok(())
+        }
+
+        /// Transfers several token types at once, `token_ids` and `values` are matched by position.
+        fn safe_batch_transfer_from(origin, from: T::AccountId, to: T::AccountId, token_ids: Vec<T::Hash>, values: Vec<T::TokenBalance>) -> Result {
+            let operator = ensure_signed(origin)?;
+            ensure!(token_ids.len() == values.len(), "token_ids and values length mismatch");
+            ensure!(token_ids.len() <= MAX_BATCH_SIZE, "Too many token types in the batch");
+            ensure!(Self::is_approved(operator.clone(), from.clone()), "You can not transfer these tokens");
+            Self::ensure_existing_account(&to)?;
+
+            // Check all the balances first, so the batch is applied entirely or not at all.
+            // A token type can be repeated, its values are summed.
+            let mut totals: Vec<(T::Hash, T::TokenBalance)> = Vec::new();
+            for (token_id, value) in token_ids.iter().zip(values.iter()) {
+                match totals.iter_mut().find(|(id, _)| id == token_id) {
+                    Some((_, total)) => *total = total.checked_add(value).ok_or("overflow in adding balance")?,
+                    None => totals.push((*token_id, *value)),
+                }
+            }
+            for (token_id, total) in totals {
+                ensure!(Self::balance_of((token_id, from.clone())) >= total, "Not enough balance.");
+                if from != to {
+                    Self::balance_of((token_id, to.clone())).checked_add(&total).ok_or("overflow in adding balance")?;
+                }
+            }
+
+            for (token_id, value) in token_ids.iter().zip(values.iter()) {
+                Self::transfer_impl(from.clone(), to.clone(), *token_id, *value)?;
+            }
+
+            Self::deposit_event(RawEvent::TransferBatch(operator, Some(from), Some(to), token_ids, values));
+
+            Ok(())
+        }
+
+        /// Enable or disable approval for a third party ("operator") to manage all of the sender's tokens.
+        fn set_approval_for_all(origin, operator: T::AccountId, approved: bool) -> Result {
+            let sender = ensure_signed(origin)?;
+            ensure!(operator != sender, "You are already implicity approved for your own actions");
+            <OperatorApprovals<T>>::insert((sender.clone(), operator.clone()), approved);
+
+            Self::deposit_event(RawEvent::ApprovalForAll(sender, operator, approved));
+
+            Ok(())
+        }
+
+        /// Create a new token type, the sender becomes its creator and receives the `initial_supply`.
+        /// An `initial_supply` of 1 that is never minted again makes the token unique.
+        fn create(origin, #[compact] initial_supply: T::TokenBalance, uri: Vec<u8>) -> Result {
+            let sender = ensure_signed(origin)?;
+            let nonce = <Nonce<T>>::get();
+            let token_id = (<system::Module<T>>::random_seed(), sender.clone(), nonce).using_encoded(<T as system::Trait>::Hashing::hash);
+
+            ensure!(!<Creators<T>>::exists(token_id), "ERC1155: token already created");
+
+            <Creators<T>>::insert(token_id, sender.clone());
+            <Nonce<T>>::mutate(|n| *n += 1);
+
+            if !uri.is_empty() {
+                Self::set_uri_impl(token_id, uri)?;
+            }
+
+            Self::mint(sender.clone(), sender, token_id, initial_supply)
+        }
+
+        /// Mint more of an existing token type, only the creator can do this.
+        fn mint_to(origin, token_id: T::Hash, to: T::AccountId, #[compact] value: T::TokenBalance) -> Result {
+            let sender = ensure_signed(origin)?;
+            let creator = Self::creator_of(token_id).ok_or("'token_id' is a invalid token type")?;
+            ensure!(sender == creator, "Only the creator can mint this token");
+
+            Self::mint(sender, to, token_id, value)
+        }
+
+        /// Change the metadata URI of a token type, only the creator can do this.
+        fn set_uri(origin, token_id: T::Hash, uri: Vec<u8>) -> Result {
+            let sender = ensure_signed(origin)?;
+            let creator = Self::creator_of(token_id).ok_or("'token_id' is a invalid token type")?;
+            ensure!(sender == creator, "Only the creator can change the uri");
+
+            Self::set_uri_impl(token_id, uri)
+        }
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as system::Trait>::AccountId,
+        Hash = <T as system::Trait>::Hash,
+        Balance = <T as self::Trait>::TokenBalance,
+    {
+        // operator, from, to, token_id, value
+        TransferSingle(AccountId, Option<AccountId>, Option<AccountId>, Hash, Balance),
+        // operator, from, to, token_ids, values
+        TransferBatch(AccountId, Option<AccountId>, Option<AccountId>, Vec<Hash>, Vec<Balance>),
+        ApprovalForAll(AccountId, AccountId, bool),
+        URI(Vec<u8>, Hash),
+    }
+);
+
+// utility and private functions
+// if marked public, accessible by other modules
+impl<T: Trait> Module<T> {
+    /// Balances of several (account, token_id) pairs, matched by position.
+    pub fn balance_of_batch(accounts: Vec<T::AccountId>, token_ids: Vec<T::Hash>) -> Vec<T::TokenBalance> {
+        accounts
+            .into_iter()
+            .zip(token_ids.into_iter())
+            .map(|(account, token_id)| Self::balance_of((token_id, account)))
+            .collect()
+    }
+
+    fn is_approved(operator: T::AccountId, owner: T::AccountId) -> bool {
+        operator == owner || Self::is_approved_for_all((owner, operator))
+    }
+
+    // But this check is not exactly the same as Ethereum, there is no `onERC1155Received`
+    fn ensure_existing_account(to: &T::AccountId) -> Result {
+        let balance_to = <balances::Module<T>>::free_balance(to);
+        ensure!(!balance_to.is_zero(), "'to' does not satisfy the `ExistentialDeposit` requirement");
+
+        Ok(())
+    }
+
+    /// internal transfer function
+    fn transfer_impl(from: T::AccountId, to: T::AccountId, token_id: T::Hash, value: T::TokenBalance) -> Result {
+        let balance_from = Self::balance_of((token_id, from.clone()));
+        ensure!(balance_from >= value, "Not enough balance.");
+
+        // update the balances
+        let new_balance_from = balance_from
+            .checked_sub(&value)
+            .ok_or("underflow in subtracting balance")?;
+        <Balances<T>>::insert((token_id, from), new_balance_from);
+
+        let balance_to = Self::balance_of((token_id, to.clone()));
+        let new_balance_to = balance_to
+            .checked_add(&value)
+            .ok_or("overflow in adding balance")?;
+        <Balances<T>>::insert((token_id, to), new_balance_to);
+
+        Ok(())
+    }
+
+    /// Internal function to mint `value` amount of an existing token type.
+    fn mint(operator: T::AccountId, to: T::AccountId, token_id: T::Hash, value: T::TokenBalance) -> Result {
+        let total_supply = Self::total_supply(token_id);
+        let new_total_supply = total_supply
+            .checked_add(&value)
+            .ok_or("Overflow when adding new tokens to total supply")?;
+
+        let balance_to = Self::balance_of((token_id, to.clone()));
+        let new_balance_to = balance_to
+            .checked_add(&value)
+            .ok_or("overflow in adding balance")?;
+
+        <TotalSupply<T>>::insert(token_id, new_total_supply);
+        <Balances<T>>::insert((token_id, to.clone()), new_balance_to);
+
+        Self::deposit_event(RawEvent::TransferSingle(operator, None, Some(to), token_id, value));
+
+        Ok(())
+    }
+
+    fn set_uri_impl(token_id: T::Hash, uri: Vec<u8>) -> Result {
+        <Uris<T>>::insert(token_id, uri.clone());
+
+        Self::deposit_event(RawEvent::URI(uri, token_id));
+
+        Ok(())
+    }
+}
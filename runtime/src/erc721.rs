@@ -1,15 +1,45 @@
-use parity_codec::Encode;
+use parity_codec::{Decode, Encode};
 /// A simple implementation of the ERC721, not include ERC165
-use rstd::prelude::Vec;
-use runtime_primitives::traits::{Hash, Zero};
+use rstd::prelude::*;
+use runtime_primitives::traits::{Hash, Verify, Zero};
 use support::{
-    decl_event, decl_module, decl_storage, dispatch::Result, ensure, StorageMap, StorageValue,
+    decl_event, decl_module, decl_storage, dispatch::Result, ensure, traits::Currency, Parameter,
+    StorageMap, StorageValue,
 };
 use system::ensure_signed;
 
+/// Maximum depth of a tree of nested tokens, bounds the walk of `root_owner_of`.
+const MAX_NEST_DEPTH: u32 = 8;
+
+/// Maximum number of tokens in a tree of nested tokens, bounds the work of moving it.
+const MAX_TREE_SIZE: usize = 64;
+
+/// Maximum number of tokens returned by one page of the enumeration queries.
+const MAX_PAGE_SIZE: u64 = 100;
+
+/// Maximum number of attributes of a token, bounds the work of moving it.
+const MAX_ATTRIBUTES: usize = 16;
+
 /// The module's configuration trait.
 pub trait Trait: balances::Trait {
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+    /// Signature of the off-chain minting vouchers
+    type Signature: Parameter + Verify<Signer = Self::AccountId>;
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
+// generic type parameters - AccountId, Hash, Balance, BlockNumber
+// Signed off-chain by an authorized minter, so a token can be listed without minting it first.
+// The signed payload is `voucher_payload`, it binds the voucher to this chain.
+pub struct MintVoucher<AccountId, Hash, Balance, BlockNumber> {
+    // The minter who signed the voucher, it receives the payment on redemption
+    creator: AccountId,
+    token_id: Hash,
+    uri: Vec<u8>,
+    min_price: Balance,
+    // Last block the voucher can be redeemed
+    expiry: BlockNumber,
 }
 
 // This module's storage items.
@@ -38,6 +68,39 @@ decl_storage! {
 
         // Not a part of the ERC721 specification, but used in random token generation
         Nonce: u64;
+
+        // Soulbound tokens: when the collection or a token is locked, it can not be
+        // transferred or approved, only burned by its owner or revoked by its issuer
+        Soulbound get(is_soulbound) config(): bool;
+        LockedTokens get(is_locked): map T::Hash => bool;
+        Issuers get(issuer_of): map T::Hash => Option<T::AccountId>;
+
+        // Lazy minting: the owner authorizes minters that sign vouchers off-chain
+        Owner get(owner) config(): T::AccountId;
+        Minters get(is_minter): map T::AccountId => bool;
+        RedeemedVouchers get(is_voucher_redeemed): map T::Hash => bool;
+        TokenUri get(token_uri): map T::Hash => Vec<u8>;
+
+        // Composable tokens: a nested token is owned by its parent token and always
+        // follows the owner of the root token
+        ParentOf get(parent_of): map T::Hash => Option<T::Hash>;
+        ChildTokens get(children_of): map T::Hash => Vec<T::Hash>;
+
+        // Numeric attributes set by the owner, e.g. a membership tier, and the sum of each
+        // attribute over all the tokens
+        Attributes get(attributes_of): map T::Hash => Vec<(Vec<u8>, u64)>;
+        AttributeTotals get(attribute_total): map Vec<u8> => u64;
+
+        // The tokens of an issuer form its collection. The number of tokens and the sum of
+        // each attribute of a collection, by owner and in total
+        IssuedBalances get(issued_balance_of): map (T::AccountId, T::AccountId) => u64;
+        IssuedSupply get(issued_supply): map T::AccountId => u64;
+        IssuedAttributes get(issued_attribute_of): map (T::AccountId, T::AccountId, Vec<u8>) => u64;
+        IssuedAttributeTotals get(issued_attribute_total): map (T::AccountId, Vec<u8>) => u64;
+
+        // Ids of the votes (e.g. of a DAO) the tokens of an account issued by an issuer are
+        // counted in, they can not be transferred until every vote released its lock
+        VoteLocks get(vote_locks): map (T::AccountId, T::AccountId) => Vec<u64>;
     }
 }
 
@@ -52,6 +115,8 @@ decl_module! {
         fn transfer_from(origin, from: T::AccountId, to: T::AccountId, token_id: T::Hash) -> Result {
             // Need to be authorized first
             let caller = ensure_signed(origin)?;
+            ensure!(!Self::is_transfer_locked(token_id), "This token is not transferable");
+            ensure!(Self::parent_of(token_id).is_none(), "Nested token must be unnested first");
             ensure!(Self::is_approved(caller, token_id), "You can not transfer this token");
 
             Self::transfer_from_impl(from, to, token_id)?;
@@ -83,6 +148,7 @@ decl_module! {
             let owner = Self::owner_of(token_id)
                .ok_or("No owner for this token")?;
 
+            ensure!(!Self::is_transfer_locked(token_id), "This token is not transferable");
             ensure!(spender != owner, "Owner is implicitly approved");
             ensure!(sender == owner || Self::is_approved_for_all((owner.clone(), sender.clone())), "You are not allowed to approve for this token");
 
@@ -93,8 +159,12 @@ decl_module! {
             Ok(())
         }
 
+        /// Make `to` an operator of all the tokens of the sender. It is only refused for a
+        /// soulbound collection: the tokens issued as locked can still be held, the locks of
+        /// single tokens are enforced when the operator transfers or approves them.
         fn set_approve_for_all(origin, to: T::AccountId, approved: bool) -> Result {
             let sender = ensure_signed(origin)?;
+            ensure!(!Self::is_soulbound(), "Tokens of this collection are not transferable");
             ensure!(to != sender, "You are already implicity approved for your own actions");
             <OperatorApprovals<T>>::insert((sender.clone(), to.clone()), approved);
 
@@ -106,14 +176,166 @@ decl_module! {
         // Not part of ERC721, but allows you to play with the runtime
         fn create_token(origin) -> Result {
             let sender = ensure_signed(origin)?;
-            let nonce = <Nonce<T>>::get();
-            let random_hash = (<system::Module<T>>::random_seed(), sender.clone(), nonce).using_encoded(<T as system::Trait>::Hashing::hash);
+            let token_id = Self::next_token_id(sender.clone());
+
+            Self::mint(sender.clone(), token_id, sender)
+        }
+
+        /// Issue a non-transferable token (e.g. a credential or membership badge) to `to`.
+        /// The sender is recorded as the issuer and is allowed to revoke it later.
+        fn create_locked_token(origin, to: T::AccountId) -> Result {
+            let sender = ensure_signed(origin)?;
+            let token_id = Self::next_token_id(sender.clone());
+
+            Self::mint(to, token_id, sender)?;
+            <LockedTokens<T>>::insert(token_id, true);
+
+            Ok(())
+        }
+
+        /// Destroy a token. The owner can always burn its own token, and the issuer
+        /// of a locked token can revoke it from the holder.
+        fn burn(origin, token_id: T::Hash) -> Result {
+            let sender = ensure_signed(origin)?;
+            let owner = Self::owner_of(token_id).ok_or("'token_id' is a invalid NFT")?;
+
+            let revoked_by_issuer = Self::is_transfer_locked(token_id)
+                && Self::issuer_of(token_id) == Some(sender.clone());
+            ensure!(sender == owner || revoked_by_issuer, "You can not burn this token");
+            ensure!(Self::children_of(token_id).is_empty(), "Token still owns other tokens");
+
+            // burned first, nothing is written when its checks fail
+            let parent = Self::parent_of(token_id);
+            Self::burn_impl(owner, token_id)?;
+            if let Some(parent) = parent {
+                Self::unnest_impl(token_id, parent)?;
+            }
+
+            Ok(())
+        }
+
+        /// Make `parent` the owner of `child`, e.g. a character owning an equipment item.
+        /// `child` and its own nested tokens then follow every transfer of `parent`.
+        fn nest(origin, child: T::Hash, parent: T::Hash) -> Result {
+            let sender = ensure_signed(origin)?;
+            ensure!(child != parent, "A token can not own itself");
+
+            let child_owner = Self::owner_of(child).ok_or("'child' is a invalid NFT")?;
+            let parent_owner = Self::owner_of(parent).ok_or("'parent' is a invalid NFT")?;
+
+            ensure!(Self::parent_of(child).is_none(), "Token is already nested, unnest it first");
+            ensure!(Self::is_approved(sender.clone(), child), "You can not nest this token");
+            ensure!(Self::is_approved(sender, parent), "You can not nest into this token");
+
+            // `parent` and all its ancestors, the child must not be one of them
+            let ancestors = Self::ancestors_of(parent)?;
+            ensure!(!ancestors.contains(&child), "Nesting would create a cycle");
+
+            let depth = ancestors.len() as u32 + Self::subtree_height(child, 0)?;
+            ensure!(depth <= MAX_NEST_DEPTH, "Nesting exceeds the maximum depth");
+
+            // `child` is not nested, so it is the root of its own tree
+            let root = ancestors[ancestors.len() - 1];
+            let size = Self::subtree_of(root).len() + Self::subtree_of(child).len();
+            ensure!(size <= MAX_TREE_SIZE, "Nesting exceeds the maximum number of nested tokens");
+
+            if child_owner != parent_owner {
+                ensure!(!Self::is_transfer_locked(child), "This token is not transferable");
+                Self::transfer_from_impl(child_owner, parent_owner, child)?;
+            }
+
+            <ParentOf<T>>::insert(child, parent);
+            <ChildTokens<T>>::mutate(parent, |children| children.push(child));
+
+            Self::deposit_event(RawEvent::Nested(child, parent));
+
+            Ok(())
+        }
+
+        /// Authorize or revoke an account to sign minting vouchers, only the owner can do this.
+        fn set_minter(origin, minter: T::AccountId, authorized: bool) -> Result {
+            let sender = ensure_signed(origin)?;
+            ensure!(sender == Self::owner(), "Only the owner can change minters");
+
+            <Minters<T>>::insert(minter.clone(), authorized);
+
+            Self::deposit_event(RawEvent::MinterChanged(minter, authorized));
+
+            Ok(())
+        }
+
+        /// Mint the token described by a voucher to the sender, paying `price` to its creator.
+        fn redeem_voucher(
+            origin,
+            voucher: MintVoucher<T::AccountId, T::Hash, T::Balance, T::BlockNumber>,
+            signature: T::Signature,
+            #[compact] price: T::Balance
+        ) -> Result {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Self::is_minter(&voucher.creator), "The voucher creator is not an authorized minter");
+            ensure!(signature.verify(&Self::voucher_payload(&voucher)[..], &voucher.creator), "Invalid voucher signature");
+            ensure!(<system::Module<T>>::block_number() <= voucher.expiry, "The voucher has expired");
+            ensure!(price >= voucher.min_price, "Price is lower than the voucher min_price");
 
-            Self::mint(sender, random_hash)?;
-            <Nonce<T>>::mutate(|n| *n += 1);
+            let voucher_hash = voucher.using_encoded(<T as system::Trait>::Hashing::hash);
+            ensure!(!Self::is_voucher_redeemed(voucher_hash), "The voucher has already been redeemed");
+            ensure!(!<OwnerOf<T>>::exists(voucher.token_id), "ERC721: token already minted");
+
+            // Every check is done above, the payment can not be followed by a failed mint
+            <balances::Module<T> as Currency<_>>::transfer(&sender, &voucher.creator, price)?;
+
+            Self::mint(sender, voucher.token_id, voucher.creator.clone())?;
+            <TokenUri<T>>::insert(voucher.token_id, voucher.uri);
+            <RedeemedVouchers<T>>::insert(voucher_hash, true);
+
+            Self::deposit_event(RawEvent::VoucherRedeemed(voucher_hash, voucher.creator, voucher.token_id));
 
             Ok(())
         }
+
+        /// Set the `key` attribute of a token to `value`, only the owner can do this.
+        fn set_attribute(origin, token_id: T::Hash, key: Vec<u8>, value: u64) -> Result {
+            let sender = ensure_signed(origin)?;
+            ensure!(sender == Self::owner(), "Only the owner can set attributes");
+            let owner = Self::owner_of(token_id).ok_or("'token_id' is a invalid NFT")?;
+
+            let previous = Self::attribute_of(token_id, &key);
+            ensure!(
+                previous > 0 || value == 0 || Self::attributes_of(token_id).len() < MAX_ATTRIBUTES,
+                "Too many attributes"
+            );
+            let total = (Self::attribute_total(&key) - previous)
+                .checked_add(value)
+                .ok_or("Overflow in the attribute total")?;
+
+            <Attributes<T>>::mutate(token_id, |attributes| {
+                attributes.retain(|(k, _)| *k != key);
+                if value > 0 {
+                    attributes.push((key.clone(), value));
+                }
+            });
+            <AttributeTotals<T>>::insert(key.clone(), total);
+            // the sums of a collection are at most the total, they can not overflow
+            if let Some(issuer) = Self::issuer_of(token_id) {
+                <IssuedAttributes<T>>::mutate((owner, issuer.clone(), key.clone()), |v| *v = *v - previous + value);
+                <IssuedAttributeTotals<T>>::mutate((issuer, key.clone()), |v| *v = *v - previous + value);
+            }
+
+            Self::deposit_event(RawEvent::AttributeSet(token_id, key, value));
+
+            Ok(())
+        }
+
+        /// Detach `child` from its parent token, it stays with the current owner. Only the
+        /// owner of the root token can do it, not the approved spenders of `child`.
+        fn unnest(origin, child: T::Hash) -> Result {
+            let sender = ensure_signed(origin)?;
+            let parent = Self::parent_of(child).ok_or("Token is not nested")?;
+            ensure!(Self::root_owner_of(child)? == sender, "You can not unnest this token");
+
+            Self::unnest_impl(child, parent)
+        }
     }
 }
 
@@ -126,19 +348,116 @@ decl_event!(
         Transfer(Option<AccountId>, Option<AccountId>, Hash),
         Approval(AccountId, AccountId, Hash),
         ApprovalForAll(AccountId, AccountId, bool),
+        // child, parent
+        Nested(Hash, Hash),
+        Unnested(Hash, Hash),
+        MinterChanged(AccountId, bool),
+        // voucher hash, creator, token_id
+        VoucherRedeemed(Hash, AccountId, Hash),
+        // token_id, key, value
+        AttributeSet(Hash, Vec<u8>, u64),
     }
 );
 
 // utility and private functions
 // if marked public, accessible by other modules
 impl<T: Trait> Module<T> {
-    /// internal transfer_from function
+    /// The payload a minter signs for a voucher. It starts with the genesis hash, so a
+    /// voucher signed for another chain does not verify here.
+    pub fn voucher_payload(
+        voucher: &MintVoucher<T::AccountId, T::Hash, T::Balance, T::BlockNumber>,
+    ) -> Vec<u8> {
+        let genesis_hash = <system::Module<T>>::block_hash(T::BlockNumber::zero());
+        (genesis_hash, voucher).encode()
+    }
+
+    /// Tokens of `owner` from index `start`, at most `limit` (capped to `MAX_PAGE_SIZE`) items.
+    pub fn tokens_of_owner(owner: T::AccountId, start: u64, limit: u64) -> Vec<T::Hash> {
+        let end = Self::page_end(start, limit, Self::balance_of(&owner));
+        (start..end)
+            .map(|i| Self::token_of_owner_by_index((owner.clone(), i)))
+            .collect()
+    }
+
+    /// All the tokens from index `start`, at most `limit` (capped to `MAX_PAGE_SIZE`) items.
+    pub fn tokens_by_index(start: u64, limit: u64) -> Vec<T::Hash> {
+        let end = Self::page_end(start, limit, Self::total_supply());
+        (start..end).map(|i| Self::token_by_index(i)).collect()
+    }
+
+    fn page_end(start: u64, limit: u64, len: u64) -> u64 {
+        start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(len)
+    }
+
+    /// Value of the `key` attribute of a token, 0 when it is not set.
+    pub fn attribute_of(token_id: T::Hash, key: &[u8]) -> u64 {
+        Self::attributes_of(token_id)
+            .into_iter()
+            .find(|(k, _)| &k[..] == key)
+            .map(|(_, value)| value)
+            .unwrap_or(0)
+    }
+
+    /// Lock the tokens of `who` issued by `issuer` while they are counted in the vote `lock_id`.
+    pub fn set_vote_lock(who: T::AccountId, issuer: T::AccountId, lock_id: u64) {
+        <VoteLocks<T>>::mutate((who, issuer), |locks| {
+            if !locks.contains(&lock_id) {
+                locks.push(lock_id);
+            }
+        });
+    }
+
+    pub fn remove_vote_lock(who: T::AccountId, issuer: T::AccountId, lock_id: u64) {
+        <VoteLocks<T>>::mutate((who, issuer), |locks| locks.retain(|l| *l != lock_id));
+    }
+
+    /// Whether `token_id` of `owner` is counted in a vote on its collection.
+    fn is_vote_locked(owner: T::AccountId, token_id: T::Hash) -> bool {
+        match Self::issuer_of(token_id) {
+            Some(issuer) => !Self::vote_locks((owner, issuer)).is_empty(),
+            None => false,
+        }
+    }
+
+    /// Walk up the chain of parent tokens and return the owner of the root token.
+    pub fn root_owner_of(token_id: T::Hash) -> rstd::result::Result<T::AccountId, &'static str> {
+        let mut current = token_id;
+        for _ in 0..=MAX_NEST_DEPTH {
+            match Self::parent_of(current) {
+                Some(parent) => current = parent,
+                None => return Self::owner_of(current).ok_or("'token_id' is a invalid NFT"),
+            }
+        }
+
+        Err("Nesting exceeds the maximum depth")
+    }
+
+    /// internal transfer_from function, moves the token together with its nested tokens
     fn transfer_from_impl(from: T::AccountId, to: T::AccountId, token_id: T::Hash) -> Result {
         // Check first
         let owner = Self::owner_of(token_id).ok_or("'token_id' is a invalid NFT")?;
 
         ensure!(owner == from, "'from' is not the owner of the NFT.");
 
+        let subtree = Self::subtree_of(token_id);
+        // otherwise the tokens could vote again from another account
+        ensure!(
+            subtree.iter().all(|t| !Self::is_vote_locked(from.clone(), *t)),
+            "The tokens of 'from' are locked by a vote"
+        );
+        ensure!(
+            subtree.iter().skip(1).all(|t| !Self::is_transfer_locked(*t)),
+            "A nested token is not transferable"
+        );
+
+        for t in subtree {
+            Self::move_token(from.clone(), to.clone(), t)?;
+        }
+
+        Ok(())
+    }
+
+    fn move_token(from: T::AccountId, to: T::AccountId, token_id: T::Hash) -> Result {
         let balance_of_from = Self::balance_of(&from);
         let balance_of_to = Self::balance_of(&to);
 
@@ -153,6 +472,8 @@ impl<T: Trait> Module<T> {
         Self::remove_token_from_owner_enumeration(from.clone(), token_id)?;
         Self::add_token_to_owner_enumeration(to.clone(), token_id)?;
         Self::remove_approval(token_id)?;
+        Self::remove_from_collection(from.clone(), token_id);
+        Self::add_to_collection(to.clone(), token_id);
 
         <Balances<T>>::insert(from.clone(), new_balance_of_from);
         <Balances<T>>::insert(to.clone(), new_balance_of_to);
@@ -163,13 +484,71 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    // Start Composable : Internal Functions //
+    fn unnest_impl(child: T::Hash, parent: T::Hash) -> Result {
+        <ChildTokens<T>>::mutate(parent, |children| children.retain(|c| *c != child));
+        <ParentOf<T>>::remove(child);
+
+        Self::deposit_event(RawEvent::Unnested(child, parent));
+
+        Ok(())
+    }
+
+    /// `token_id` followed by its parent, grand parent, ... up to the root token.
+    fn ancestors_of(token_id: T::Hash) -> rstd::result::Result<Vec<T::Hash>, &'static str> {
+        let mut ancestors = Vec::new();
+        ancestors.push(token_id);
+        let mut current = token_id;
+        while let Some(parent) = Self::parent_of(current) {
+            ensure!(ancestors.len() as u32 <= MAX_NEST_DEPTH, "Nesting exceeds the maximum depth");
+            ancestors.push(parent);
+            current = parent;
+        }
+
+        Ok(ancestors)
+    }
+
+    /// Number of levels of tokens nested below `token_id`, 0 for a token without children.
+    fn subtree_height(token_id: T::Hash, depth: u32) -> rstd::result::Result<u32, &'static str> {
+        ensure!(depth <= MAX_NEST_DEPTH, "Nesting exceeds the maximum depth");
+
+        let mut height = 0;
+        for child in Self::children_of(token_id) {
+            height = height.max(Self::subtree_height(child, depth + 1)? + 1);
+        }
+
+        Ok(height)
+    }
+
+    /// `token_id` and all the tokens nested below it, parents before children.
+    fn subtree_of(token_id: T::Hash) -> Vec<T::Hash> {
+        let mut subtree = Vec::new();
+        subtree.push(token_id);
+
+        let mut i = 0;
+        while i < subtree.len() {
+            let children = Self::children_of(subtree[i]);
+            subtree.extend(children);
+            i += 1;
+        }
+
+        subtree
+    }
+    // End Composable : Internal Functions //
+
     fn remove_approval(token_id: T::Hash) -> Result {
         <Approvals<T>>::remove(token_id);
 
         Ok(())
     }
 
-    fn is_approved(caller: T::AccountId, token_id: T::Hash) -> bool {
+    /// A token is locked if the whole collection is soulbound or it was issued as locked
+    fn is_transfer_locked(token_id: T::Hash) -> bool {
+        Self::is_soulbound() || Self::is_locked(token_id)
+    }
+
+    /// Whether `caller` is the owner, an authorized operator or the approved address of the token
+    pub fn is_approved(caller: T::AccountId, token_id: T::Hash) -> bool {
         let owner = Self::owner_of(&token_id);
         let approved_user = Self::get_approved(&token_id);
 
@@ -217,6 +596,27 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    fn remove_token_from_all_tokens_enumeration(token_id: T::Hash) -> Result {
+        let total_supply = Self::total_supply();
+        let last_token_index = total_supply
+            .checked_sub(1)
+            .ok_or("Underflow when removing token from total supply")?;
+        let token_index = <TokensIndex<T>>::get(&token_id);
+
+        // Swap and pop
+        if token_index != last_token_index {
+            let last_token_id = <Tokens<T>>::get(last_token_index);
+            <Tokens<T>>::insert(token_index, last_token_id);
+            <TokensIndex<T>>::insert(last_token_id, token_index);
+        }
+
+        <Tokens<T>>::remove(last_token_index);
+        <TokensIndex<T>>::remove(token_id);
+        <TotalSupply<T>>::put(last_token_index);
+
+        Ok(())
+    }
+
     fn remove_token_from_owner_enumeration(from: T::AccountId, token_id: T::Hash) -> Result {
         let balance_of_from = Self::balance_of(&from);
         let last_token_index = balance_of_from
@@ -238,8 +638,27 @@ impl<T: Trait> Module<T> {
     }
     // End ERC721 : Enumerable : Internal Functions //
 
-    /// Internal function to mint a new token.
-    fn mint(to: T::AccountId, token_id: T::Hash) -> Result {
+    /// Count `token_id` and its attributes in the collection balances of `owner`.
+    fn add_to_collection(owner: T::AccountId, token_id: T::Hash) {
+        if let Some(issuer) = Self::issuer_of(token_id) {
+            <IssuedBalances<T>>::mutate((owner.clone(), issuer.clone()), |balance| *balance += 1);
+            for (key, value) in Self::attributes_of(token_id) {
+                <IssuedAttributes<T>>::mutate((owner.clone(), issuer.clone(), key), |v| *v += value);
+            }
+        }
+    }
+
+    fn remove_from_collection(owner: T::AccountId, token_id: T::Hash) {
+        if let Some(issuer) = Self::issuer_of(token_id) {
+            <IssuedBalances<T>>::mutate((owner.clone(), issuer.clone()), |balance| *balance -= 1);
+            for (key, value) in Self::attributes_of(token_id) {
+                <IssuedAttributes<T>>::mutate((owner.clone(), issuer.clone(), key), |v| *v -= value);
+            }
+        }
+    }
+
+    /// Internal function to mint a new token in the collection of `issuer`.
+    fn mint(to: T::AccountId, token_id: T::Hash, issuer: T::AccountId) -> Result {
         ensure!(
             !<OwnerOf<T>>::exists(token_id),
             "ERC721: token already minted"
@@ -256,9 +675,58 @@ impl<T: Trait> Module<T> {
 
         <OwnerOf<T>>::insert(token_id.clone(), to.clone());
         <Balances<T>>::insert(to.clone(), new_balance_of);
+        <Issuers<T>>::insert(token_id, issuer.clone());
+        <IssuedSupply<T>>::mutate(issuer, |supply| *supply += 1);
+        Self::add_to_collection(to.clone(), token_id);
 
         Self::deposit_event(RawEvent::Transfer(None, Some(to), token_id));
 
         Ok(())
     }
+
+    /// Internal function to burn a token.
+    fn burn_impl(owner: T::AccountId, token_id: T::Hash) -> Result {
+        // otherwise the supply counted by a vote would change
+        ensure!(!Self::is_vote_locked(owner.clone(), token_id), "The tokens of the owner are locked by a vote");
+        let balance_of = Self::balance_of(&owner);
+
+        let new_balance_of = balance_of
+            .checked_sub(1)
+            .ok_or("Underflow subtracting a token from account balance")?;
+
+        Self::remove_token_from_owner_enumeration(owner.clone(), token_id)?;
+        Self::remove_token_from_all_tokens_enumeration(token_id)?;
+        Self::remove_approval(token_id)?;
+
+        Self::remove_from_collection(owner.clone(), token_id);
+        let issuer = <Issuers<T>>::take(token_id);
+        if let Some(ref issuer) = issuer {
+            <IssuedSupply<T>>::mutate(issuer, |supply| *supply -= 1);
+        }
+
+        <Balances<T>>::insert(owner.clone(), new_balance_of);
+        <OwnerOf<T>>::remove(token_id);
+        <LockedTokens<T>>::remove(token_id);
+        <TokenUri<T>>::remove(token_id);
+        for (key, value) in <Attributes<T>>::take(token_id) {
+            if let Some(ref issuer) = issuer {
+                <IssuedAttributeTotals<T>>::mutate((issuer.clone(), key.clone()), |total| *total -= value);
+            }
+            <AttributeTotals<T>>::mutate(key, |total| *total -= value);
+        }
+
+        Self::deposit_event(RawEvent::Transfer(Some(owner), None, token_id));
+
+        Ok(())
+    }
+
+    /// Generate a fresh token id from the random seed, the creator and a nonce.
+    fn next_token_id(sender: T::AccountId) -> T::Hash {
+        let nonce = <Nonce<T>>::get();
+        let random_hash = (<system::Module<T>>::random_seed(), sender, nonce)
+            .using_encoded(<T as system::Trait>::Hashing::hash);
+        <Nonce<T>>::mutate(|n| *n += 1);
+
+        random_hash
+    }
 }
@@ -1,7 +1,8 @@
+use crate::clock::Clock;
 use crate::lockabletoken as token;
 use parity_codec::{Decode, Encode};
 use rstd::prelude::*;
-use runtime_primitives::traits::{As, Bounded};
+use runtime_primitives::traits::{As, Bounded, CheckedAdd};
 use support::traits::{LockableCurrency, WithdrawReasons};
 use support::{
     decl_event, decl_module, decl_storage, dispatch::Result, ensure, StorageMap, StorageValue,
@@ -26,6 +27,21 @@ pub struct Crowdsale<AccountId, TokenBalance, Moment> {
     funding_goal_reached: bool,
     crowdsale_closed: bool,
     price: u64,
+    // `deadline` is a block number with `Clock::BlockNumber`
+    clock: Clock,
+}
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+// A sale round of a crowdsale, `start` and `end` are measured with the crowdsale clock.
+pub struct Round<TokenBalance, Moment> {
+    start: Moment,
+    end: Moment,
+    price: u64,
+    // Tokens for sale in this round, the unsold ones are carried into the next round
+    cap: TokenBalance,
+    // Extra tokens given on top of each purchase, in percent
+    bonus_percent: u32,
 }
 
 const PAY_ID: [u8; 8] = *b"exchange";
@@ -34,6 +50,12 @@ decl_storage! {
     trait Store for Module<T: Trait> as Ico {
         Crowdsales get(crowdsales) : map u64 => Crowdsale<T::AccountId, T::TokenBalance, T::Moment>;
         CrowdsaleCount get(crowdsale_count) : u64 = 0;
+        // Sale rounds of a crowdsale, sorted and not overlapping
+        Rounds get(rounds) : map u64 => Vec<Round<T::TokenBalance, T::Moment>>;
+        // Tokens sold in a round of a crowdsale, bonuses included
+        RoundSold get(round_sold) : map (u64, u32) => T::TokenBalance;
+        // Tokens sold by a crowdsale in all its rounds, or without rounds
+        TokensSold get(tokens_sold) : map u64 => T::TokenBalance;
     }
 }
 
@@ -51,7 +73,10 @@ decl_event!(
         FundUnlock(u64, AccountId, Option<TokenBalance>),
         FundLock(u64, AccountId, TokenBalance),
 
-        PayToken(u64, AccountId, TokenBalance),
+        // crowdsale_id, round_id
+        RoundAdded(u64, u32),
+        // crowdsale_id, round_id, buyer, value
+        PayToken(u64, Option<u32>, AccountId, TokenBalance),
         Withdraw(u64, AccountId, TokenBalance),
     }
 );
@@ -61,6 +86,8 @@ decl_module! {
     // initialize events for this module
     fn deposit_event<T>() = default;
 
+    /// Create a crowdsale of a new token, it ends after `duration_in_seconds`, or after
+    /// as many blocks with `Clock::BlockNumber`.
     pub fn create_crowdsale(
         origin,
         if_successful_send_to: T::AccountId,
@@ -70,17 +97,19 @@ decl_module! {
         token_name: Vec<u8>,
         token_symbol: Vec<u8>,
         token_total_supply: T::TokenBalance,
-        token_decimal: u64) -> Result {
+        token_decimal: u64,
+        clock: Clock) -> Result {
             let sender = ensure_signed(origin)?;
 
             let c = Crowdsale{
                 beneficiary: if_successful_send_to,
                 funding_goal: funding_goal_in_turs,
                 amount_raised: T::TokenBalance::sa(0),
-                deadline: duration_in_seconds + <timestamp::Module<T>>::get(),
+                deadline: duration_in_seconds + clock.now::<T>(),
                 funding_goal_reached: false,
                 crowdsale_closed: false,
-                price
+                price,
+                clock
             };
 
         let id = Self::crowdsale_count();
@@ -95,13 +124,77 @@ decl_module! {
         create_token_result
     }
 
-    /// exchange balance to tokens
+    /// Add a sale round to a crowdsale, only its creator can do this. The rounds follow
+    /// each other and must be added before they start and before the first sale. The
+    /// caps of the rounds can not exceed the tokens of the creator.
+    fn add_round(
+        origin,
+        crowdsale_id: u64,
+        start: T::Moment,
+        end: T::Moment,
+        price: u64,
+        cap: T::TokenBalance,
+        bonus_percent: u32
+    ) -> Result {
+        let sender = ensure_signed(origin)?;
+        ensure!(crowdsale_id < Self::crowdsale_count(), "This crowdsale does not exist");
+        ensure!(sender == <token::Module<T>>::owners(crowdsale_id), "Only the creator can add rounds");
+        ensure!(price > 0, "The price can not be zero");
+
+        // otherwise the sales without rounds would stop outside the new round
+        ensure!(Self::tokens_sold(crowdsale_id) == T::TokenBalance::sa(0), "The sales have already started");
+
+        let c = Self::crowdsales(crowdsale_id);
+        ensure!(start >= c.clock.now::<T>(), "The round has already started");
+        ensure!(start < end && end <= c.deadline, "Invalid round period");
+
+        let mut rounds = Self::rounds(crowdsale_id);
+        if let Some(last) = rounds.last() {
+            ensure!(start >= last.end, "Rounds can not overlap");
+        }
+        let caps = rounds
+            .iter()
+            .try_fold(cap, |caps, round| caps.checked_add(&round.cap))
+            .ok_or("Overflow in the round caps")?;
+        ensure!(
+            caps <= <token::Module<T>>::balance_of((crowdsale_id, sender)),
+            "The round caps exceed the tokens of the creator"
+        );
+        let round_id = rounds.len() as u32;
+        rounds.push(Round { start, end, price, cap, bonus_percent });
+        <Rounds<T>>::insert(crowdsale_id, rounds);
+
+        Self::deposit_event(RawEvent::RoundAdded(crowdsale_id, round_id));
+
+        Ok(())
+    }
+
+    /// exchange balance to tokens, at the price of the active round if the crowdsale has rounds
     fn pay(origin, crowdsale_id: u64, value: T::TokenBalance) -> Result{
         let sender = ensure_signed(origin)?;
 
         let c = Self::crowdsales(crowdsale_id);
         ensure!(!c.crowdsale_closed, "crowsale has already been closed");
 
+        let rounds = Self::rounds(crowdsale_id);
+        let round_id = if rounds.is_empty() {
+            None
+        } else {
+            let now = c.clock.now::<T>();
+            let index = rounds.iter().position(|r| r.start <= now && now < r.end).ok_or("No round is active")?;
+            Some(index as u32)
+        };
+        let tokens = match round_id {
+            None => value / T::TokenBalance::sa(c.price.into()),
+            Some(id) => {
+                let round = &rounds[id as usize];
+                let tokens = value / T::TokenBalance::sa(round.price);
+                let tokens = tokens + tokens * T::TokenBalance::sa(round.bonus_percent.into()) / T::TokenBalance::sa(100);
+                ensure!(tokens <= Self::round_available(crowdsale_id, id), "Not enough tokens left in this round");
+                tokens
+            }
+        };
+
         let value_to_tokenbalance = <Balance<T> as As<u64>>::sa(T::TokenBalance::as_(value));
 
         // lock balance
@@ -116,10 +209,14 @@ decl_module! {
         // add to token
         let owner = <token::Module<T>>::owners(crowdsale_id);
 
-        let tranfer_impl_result = <token::Module<T>>::transfer_impl(crowdsale_id, owner, sender.clone(), value / T::TokenBalance::sa(c.price.into()));
+        let tranfer_impl_result = <token::Module<T>>::transfer_impl(crowdsale_id, owner, sender.clone(), tokens);
 
         if tranfer_impl_result.is_ok(){
-            Self::deposit_event(RawEvent::PayToken(crowdsale_id, sender, value));
+            if let Some(id) = round_id {
+                <RoundSold<T>>::mutate((crowdsale_id, id), |sold| *sold += tokens);
+            }
+            <TokensSold<T>>::mutate(crowdsale_id, |sold| *sold += tokens);
+            Self::deposit_event(RawEvent::PayToken(crowdsale_id, round_id, sender, value));
         }
 
         Ok(())
@@ -191,13 +288,24 @@ decl_module! {
 }
 
 impl<T: Trait> Module<T> {
+    /// Tokens left for sale in a round, including the unsold tokens of the previous rounds.
+    pub fn round_available(crowdsale_id: u64, round_id: u32) -> T::TokenBalance {
+        Self::rounds(crowdsale_id)
+            .iter()
+            .take(round_id as usize + 1)
+            .enumerate()
+            .fold(T::TokenBalance::sa(0), |available, (i, round)| {
+                available + round.cap - Self::round_sold((crowdsale_id, i as u32))
+            })
+    }
+
     fn check_goal_reached(crowdsale_id: u64) -> Result {
         let mut c = Self::crowdsales(crowdsale_id);
         if c.crowdsale_closed {
             return Ok(());
         }
         ensure!(
-            <timestamp::Module<T>>::get() >= c.deadline,
+            c.clock.now::<T>() >= c.deadline,
             "It's not the deadline yet"
         );
 
@@ -282,6 +390,7 @@ mod tests {
     type Ico = Module<Test>;
     type Token = token::Module<Test>;
     type Timestamp = timestamp::Module<Test>;
+    type System = system::Module<Test>;
     type Balances = balances::Module<Test>;
 
     // builds the genesis config store and sets mock values
@@ -320,7 +429,8 @@ mod tests {
                 "ABMatrix Token".as_bytes().into(),
                 "ABT".as_bytes().into(),
                 1000,
-                18
+                18,
+                Clock::Timestamp
             ));
         });
     }
@@ -337,7 +447,8 @@ mod tests {
                 "ABMatrix Token".as_bytes().into(),
                 "ABT".as_bytes().into(),
                 1000,
-                18
+                18,
+                Clock::Timestamp
             ));
             assert_ok!(Ico::pay(Origin::signed(1), 0, 100));
 
@@ -363,7 +474,8 @@ mod tests {
                 "ABMatrix Token".as_bytes().into(),
                 "ABT".as_bytes().into(),
                 1000,
-                18
+                18,
+                Clock::Timestamp
             ));
             assert_ok!(Ico::pay(Origin::signed(1), 0, 100));
             assert_ok!(Ico::invest(Origin::signed(1), 0, 100));
@@ -382,7 +494,8 @@ mod tests {
                 "ABMatrix Token".as_bytes().into(),
                 "ABT".as_bytes().into(),
                 1000,
-                18
+                18,
+                Clock::Timestamp
             ));
             assert_ok!(Ico::pay(Origin::signed(1), 0, 100));
             assert_ok!(Ico::invest(Origin::signed(1), 0, 100));
@@ -404,7 +517,8 @@ mod tests {
                 "ABMatrix Token".as_bytes().into(),
                 "ABT".as_bytes().into(),
                 1000,
-                18
+                18,
+                Clock::Timestamp
             ));
             assert_ok!(Ico::pay(Origin::signed(1), 0, 100));
             assert_ok!(Ico::invest(Origin::signed(1), 0, 100));
@@ -431,7 +545,8 @@ mod tests {
                 "ABMatrix Token".as_bytes().into(),
                 "ABT".as_bytes().into(),
                 1000,
-                18
+                18,
+                Clock::Timestamp
             ));
             assert_eq!(Token::balance_of((0, 1)), 1000);
             assert_ok!(Ico::pay(Origin::signed(1), 0, 100));
@@ -448,4 +563,76 @@ mod tests {
             assert_eq!(Token::balance_of((0, 1)), 1100);
         });
     }
+
+    #[test]
+    fn should_end_crowdsale_at_block_number() {
+        with_externalities(&mut new_test_ext(), || {
+            System::set_block_number(1);
+            assert_ok!(Ico::create_crowdsale(
+                Origin::signed(1),
+                2,
+                100,
+                10,
+                1,
+                "ABMatrix Token".as_bytes().into(),
+                "ABT".as_bytes().into(),
+                1000,
+                18,
+                Clock::BlockNumber
+            ));
+            assert_eq!(Ico::crowdsales(0).deadline, 11);
+
+            Timestamp::set_timestamp(11);
+            assert_noop!(Ico::check_goal_reached(0), "It's not the deadline yet");
+
+            System::set_block_number(11);
+            assert_ok!(Ico::check_goal_reached(0));
+            assert_eq!(Ico::crowdsales(0).crowdsale_closed, true);
+        });
+    }
+
+    #[test]
+    fn should_sell_in_rounds() {
+        with_externalities(&mut new_test_ext(), || {
+            assert_ok!(Ico::create_crowdsale(
+                Origin::signed(1),
+                2,
+                100,
+                10,
+                1,
+                "ABMatrix Token".as_bytes().into(),
+                "ABT".as_bytes().into(),
+                1000,
+                18,
+                Clock::Timestamp
+            ));
+            assert_noop!(Ico::add_round(Origin::signed(2), 0, 0, 5, 2, 100, 10), "Only the creator can add rounds");
+            assert_ok!(Ico::add_round(Origin::signed(1), 0, 0, 5, 2, 100, 10));
+            assert_noop!(Ico::add_round(Origin::signed(1), 0, 3, 8, 4, 50, 0), "Rounds can not overlap");
+            assert_noop!(
+                Ico::add_round(Origin::signed(1), 0, 5, 10, 4, 901, 0),
+                "The round caps exceed the tokens of the creator"
+            );
+            assert_ok!(Ico::add_round(Origin::signed(1), 0, 5, 10, 4, 50, 0));
+
+            // 50 tokens and a bonus of 10%
+            assert_ok!(Ico::pay(Origin::signed(2), 0, 100));
+            assert_eq!(Ico::tokens_sold(0), 55);
+            assert_noop!(Ico::add_round(Origin::signed(1), 0, 10, 10, 4, 10, 0), "The sales have already started");
+            assert_eq!(Token::balance_of((0, 2)), 55);
+            assert_eq!(Ico::round_available(0, 0), 45);
+            assert_noop!(Ico::pay(Origin::signed(2), 0, 200), "Not enough tokens left in this round");
+
+            // the 45 unsold tokens are carried into the next round
+            Timestamp::set_timestamp(5);
+            assert_eq!(Ico::round_available(0, 1), 95);
+            assert_ok!(Ico::pay(Origin::signed(2), 0, 360));
+            assert_eq!(Token::balance_of((0, 2)), 145);
+            assert_eq!(Ico::round_sold((0, 1)), 90);
+            assert_eq!(Ico::round_available(0, 1), 5);
+
+            Timestamp::set_timestamp(10);
+            assert_noop!(Ico::pay(Origin::signed(2), 0, 100), "No round is active");
+        });
+    }
 }
@@ -6,13 +6,13 @@
 
 use client::{
     block_builder::api::{self as block_builder_api, CheckInherentsResult, InherentData},
-    impl_runtime_apis, runtime_api,
+    decl_runtime_apis, impl_runtime_apis, runtime_api,
 };
 #[cfg(feature = "std")]
 use council::seats as council_seats;
 use council::{motions as council_motions, voting as council_voting};
 use grandpa::fg_primitives::{self, ScheduledChange};
-use parity_codec::{Decode, Encode};
+use parity_codec::{Codec, Decode, Encode};
 #[cfg(feature = "std")]
 use primitives::bytes;
 use primitives::u32_trait::{_2, _4};
@@ -35,7 +35,9 @@ use version::RuntimeVersion;
 
 // A few exports that help ease life for downstream crates.
 pub use balances::Call as BalancesCall;
+pub use clock::Clock;
 pub use consensus::Call as ConsensusCall;
+pub use dao::ProposalInfo;
 #[cfg(any(feature = "std", test))]
 pub use runtime_primitives::BuildStorage;
 pub use runtime_primitives::{Perbill, Permill};
@@ -65,8 +67,10 @@ pub type BlockNumber = u64;
 /// Index of an account's extrinsic in the chain.
 pub type Nonce = u64;
 
+mod clock;
 mod dao;
 mod daotoken;
+mod erc1155;
 mod erc1400;
 mod erc20;
 mod erc721;
@@ -109,6 +113,50 @@ pub mod opaque {
     pub type SessionKey = AuthorityId;
 }
 
+decl_runtime_apis! {
+    /// Queries over the `erc721` module, so a client can list tokens in one round trip.
+    pub trait Erc721Api<AccountId, Hash> where
+        AccountId: Codec,
+        Hash: Codec,
+    {
+        /// Tokens owned by `account` from index `start`, at most `limit` items.
+        fn tokens_of_owner(account: AccountId, start: u64, limit: u64) -> Vec<Hash>;
+        /// All the tokens from index `start`, at most `limit` items.
+        fn tokens_by_index(start: u64, limit: u64) -> Vec<Hash>;
+        fn balance_of(account: AccountId) -> u64;
+        fn total_supply() -> u64;
+        fn owner_of(token_id: Hash) -> Option<AccountId>;
+        fn get_approved(token_id: Hash) -> Option<AccountId>;
+        fn is_approved_for_all(owner: AccountId, operator: AccountId) -> bool;
+        /// Whether `account` is allowed to transfer `token_id`.
+        fn is_approved(account: AccountId, token_id: Hash) -> bool;
+    }
+
+    /// Queries over the `dao` module: proposals with their tally and the state of a voter.
+    pub trait DaoApi<AccountId, Balance, Moment, Hash> where
+        AccountId: Codec,
+        Balance: Codec,
+        Moment: Codec,
+        Hash: Codec,
+    {
+        /// Proposal ids of the DAO from match `start`, at most `limit` items. `open`
+        /// selects the open or the closed proposals, `None` returns both.
+        fn proposals(dao_id: u64, open: Option<bool>, start: u64, limit: u64) -> Vec<u64>;
+        /// The proposal with its tally, quorum requirement and time remaining.
+        fn proposal(proposal_id: u64) -> Option<ProposalInfo<AccountId, Balance, Moment, Hash>>;
+        /// Votes of `account` from index `start`, at most `limit` items.
+        fn voter_history(account: AccountId, start: u64, limit: u64) -> Vec<(u64, Option<bool>, Balance)>;
+        /// The open proposal `account` voted on which blocks it from splitting or ragequitting.
+        fn blocked_by(dao_id: u64, account: AccountId) -> Option<u64>;
+        fn voting_power(dao_id: u64, account: AccountId, topic: Option<u32>) -> Balance;
+        fn delegation_graph(dao_id: u64, account: AccountId, topic: Option<u32>) -> Vec<(AccountId, AccountId)>;
+        /// The keyless account holding the native currency of the DAO.
+        fn treasury_account(dao_id: u64) -> AccountId;
+        /// The keyless account holding the reward pool of the DAO.
+        fn reward_account(dao_id: u64) -> AccountId;
+    }
+}
+
 /// This runtime version.
 pub const VERSION: RuntimeVersion = RuntimeVersion {
     spec_name: create_runtime_str!("turing-node"),
@@ -277,6 +325,12 @@ impl erc20::Trait for Runtime {
 
 impl erc721::Trait for Runtime {
     type Event = Event;
+    type Signature = AccountSignature;
+}
+
+impl erc1155::Trait for Runtime {
+    type Event = Event;
+    type TokenBalance = u128;
 }
 
 impl daotoken::Trait for Runtime {
@@ -286,6 +340,10 @@ impl daotoken::Trait for Runtime {
 
 impl dao::Trait for Runtime {
     type Event = Event;
+    type Origin = Origin;
+    type Proposal = Call;
+    type ExternalOrigin = dao::EnsureDao;
+    type Currency = Balances;
 }
 
 impl lockabletoken::Trait for Runtime {
@@ -330,8 +388,9 @@ construct_runtime!(
 		// Used for the module erc20 in `./erc20.rs`
 		ERC20: erc20::{Module, Call, Storage, Event<T>, Config<T>},
 		ERC721: erc721::{Module, Call, Storage, Event<T>, Config<T>},
+		ERC1155: erc1155::{Module, Call, Storage, Event<T>, Config<T>},
 		DaoToken: daotoken::{Module, Call, Storage, Event<T>, Config<T>},
-		Dao: dao::{Module, Call, Storage, Event<T>, Config<T>},
+		Dao: dao::{Module, Call, Storage, Event<T>, Config<T>, Origin},
 		LockableToken: lockabletoken::{Module, Call, Storage, Event<T>},
 		Ico: ico::{Module, Call, Storage, Event<T>},
         ERC1400: erc1400::{Module, Call, Storage, Event<T>, Config<T>},
@@ -462,4 +521,72 @@ impl_runtime_apis! {
             Consensus::authorities()
         }
     }
+
+    impl Erc721Api<Block, AccountId, Hash> for Runtime {
+        fn tokens_of_owner(account: AccountId, start: u64, limit: u64) -> Vec<Hash> {
+            ERC721::tokens_of_owner(account, start, limit)
+        }
+
+        fn tokens_by_index(start: u64, limit: u64) -> Vec<Hash> {
+            ERC721::tokens_by_index(start, limit)
+        }
+
+        fn balance_of(account: AccountId) -> u64 {
+            ERC721::balance_of(account)
+        }
+
+        fn total_supply() -> u64 {
+            ERC721::total_supply()
+        }
+
+        fn owner_of(token_id: Hash) -> Option<AccountId> {
+            ERC721::owner_of(token_id)
+        }
+
+        fn get_approved(token_id: Hash) -> Option<AccountId> {
+            ERC721::get_approved(token_id)
+        }
+
+        fn is_approved_for_all(owner: AccountId, operator: AccountId) -> bool {
+            ERC721::is_approved_for_all((owner, operator))
+        }
+
+        fn is_approved(account: AccountId, token_id: Hash) -> bool {
+            ERC721::is_approved(account, token_id)
+        }
+    }
+
+    impl DaoApi<Block, AccountId, u128, u64, Hash> for Runtime {
+        fn proposals(dao_id: u64, open: Option<bool>, start: u64, limit: u64) -> Vec<u64> {
+            Dao::proposals_page(dao_id, open, start, limit)
+        }
+
+        fn proposal(proposal_id: u64) -> Option<ProposalInfo<AccountId, u128, u64, Hash>> {
+            Dao::proposal_info(proposal_id)
+        }
+
+        fn voter_history(account: AccountId, start: u64, limit: u64) -> Vec<(u64, Option<bool>, u128)> {
+            Dao::voter_history(account, start, limit)
+        }
+
+        fn blocked_by(dao_id: u64, account: AccountId) -> Option<u64> {
+            Dao::blocked_by(dao_id, account)
+        }
+
+        fn voting_power(dao_id: u64, account: AccountId, topic: Option<u32>) -> u128 {
+            Dao::voting_power(dao_id, account, topic)
+        }
+
+        fn delegation_graph(dao_id: u64, account: AccountId, topic: Option<u32>) -> Vec<(AccountId, AccountId)> {
+            Dao::delegation_graph(dao_id, account, topic, None)
+        }
+
+        fn treasury_account(dao_id: u64) -> AccountId {
+            Dao::treasury_account(dao_id)
+        }
+
+        fn reward_account(dao_id: u64) -> AccountId {
+            Dao::reward_account(dao_id)
+        }
+    }
 }
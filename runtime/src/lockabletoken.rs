@@ -39,6 +39,13 @@ decl_storage! {
         // special interface
         LockedTokens get(locked_tokens): map (u64, T::AccountId) => T::TokenBalance;
         TotalLocked get(total_locked): map u64 => T::TokenBalance;
+        // (ico_id, proposal_id) => deposit, used when the token governs a DAO
+        LockedDeposits get(locked_deposits): map (u64, u64) => T::TokenBalance;
+        // Tokens locked by the conviction votes of each DAO governed by the token, they can
+        // not be transferred. (ico_id, dao_id, account) => locked
+        VoteLocks get(vote_lock): map (u64, u64, T::AccountId) => T::TokenBalance;
+        // The DAOs holding a vote lock on the tokens of an account
+        VoteLockDaos get(vote_lock_daos): map (u64, T::AccountId) => Vec<u64>;
     }
 }
 
@@ -52,6 +59,7 @@ decl_module! {
         /// Transfers token from the sender to the `to` address.
         fn transfer(origin, ico_id: u64, to: T::AccountId, #[compact] value: T::TokenBalance) -> Result {
             let sender = ensure_signed(origin)?;
+            Self::ensure_unlocked(ico_id, sender.clone(), value)?;
             Self::transfer_impl(ico_id, sender, to, value)
         }
 
@@ -84,6 +92,7 @@ decl_module! {
 
             Self::deposit_event(RawEvent::Approval(from.clone(), caller.clone(), value));
 
+            Self::ensure_unlocked(ico_id, from.clone(), value)?;
             Self::transfer_impl(ico_id, from, to, value)
         }
     }
@@ -125,6 +134,45 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    pub fn total_supply(ico_id: u64) -> T::TokenBalance {
+        Self::token_details(ico_id).total_supply
+    }
+
+    /// Tokens of `who` locked by votes, the largest lock of the DAOs governed by the token.
+    pub fn locked_balance_of(ico_id: u64, who: T::AccountId) -> T::TokenBalance {
+        Self::vote_lock_daos((ico_id, who.clone()))
+            .into_iter()
+            .map(|dao_id| Self::vote_lock((ico_id, dao_id, who.clone())))
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Check that `value` tokens of `from` are not locked by a vote.
+    fn ensure_unlocked(ico_id: u64, from: T::AccountId, value: T::TokenBalance) -> Result {
+        let unlocked = Self::balance_of((ico_id, from.clone()))
+            .checked_sub(&Self::locked_balance_of(ico_id, from))
+            .unwrap_or_default();
+        ensure!(unlocked >= value, "Not enough unlocked balance.");
+
+        Ok(())
+    }
+
+    /// Lock `value` tokens of `who` against transfers for the DAO `dao_id`, replacing the
+    /// previous lock of this DAO. The locks of the other DAOs are kept.
+    pub fn set_vote_lock(ico_id: u64, dao_id: u64, who: T::AccountId, value: T::TokenBalance) {
+        <VoteLockDaos<T>>::mutate((ico_id, who.clone()), |daos| {
+            if !daos.contains(&dao_id) {
+                daos.push(dao_id);
+            }
+        });
+        <VoteLocks<T>>::insert((ico_id, dao_id, who), value);
+    }
+
+    pub fn remove_vote_lock(ico_id: u64, dao_id: u64, who: T::AccountId) {
+        <VoteLockDaos<T>>::mutate((ico_id, who.clone()), |daos| daos.retain(|d| *d != dao_id));
+        <VoteLocks<T>>::remove((ico_id, dao_id, who));
+    }
+
     /// internal transfer function
     pub fn transfer_impl(
         ico_id: u64,
@@ -197,4 +245,81 @@ impl<T: Trait> Module<T> {
 
         Ok(())
     }
+
+    /// Destroy `value` tokens of `from`, reducing the total supply.
+    pub fn burn(ico_id: u64, from: T::AccountId, value: T::TokenBalance) -> Result {
+        let balance_from = Self::balance_of((ico_id, from.clone()));
+        ensure!(balance_from >= value, "Not enough balance.");
+
+        let updated_balance_from = balance_from
+            .checked_sub(&value)
+            .ok_or("underflow in subtracting balance")?;
+        let mut token = Self::token_details(ico_id);
+        token.total_supply = token
+            .total_supply
+            .checked_sub(&value)
+            .ok_or("underflow in subtracting total supply")?;
+
+        <Balances<T>>::insert((ico_id, from), updated_balance_from);
+        <Tokens<T>>::insert(ico_id, token);
+
+        Ok(())
+    }
+
+    /// Create `value` tokens for `to`, increasing the total supply.
+    pub fn mint(ico_id: u64, to: T::AccountId, value: T::TokenBalance) -> Result {
+        let balance_to = Self::balance_of((ico_id, to.clone()));
+        let updated_balance_to = balance_to
+            .checked_add(&value)
+            .ok_or("overflow in adding balance")?;
+        let mut token = Self::token_details(ico_id);
+        token.total_supply = token
+            .total_supply
+            .checked_add(&value)
+            .ok_or("overflow in adding total supply")?;
+
+        <Balances<T>>::insert((ico_id, to), updated_balance_to);
+        <Tokens<T>>::insert(ico_id, token);
+
+        Ok(())
+    }
+
+    /// Lock a proposal deposit of a DAO governed by this token.
+    pub fn lock_deposit(ico_id: u64, from: T::AccountId, value: T::TokenBalance, proposal_id: u64) -> Result {
+        ensure!(
+            <Balances<T>>::exists((ico_id, from.clone())),
+            "This account does not own this token"
+        );
+
+        let balance_from = Self::balance_of((ico_id, from.clone()));
+        ensure!(balance_from > value, "Not enough balance.");
+        let updated_balance_from = balance_from
+            .checked_sub(&value)
+            .ok_or("overflow in subtracting balance")?;
+        let deposit = Self::locked_deposits((ico_id, proposal_id));
+        let updated_deposit = deposit
+            .checked_add(&value)
+            .ok_or("overflow in adding deposit")?;
+
+        <Balances<T>>::insert((ico_id, from), updated_balance_from);
+        <LockedDeposits<T>>::insert((ico_id, proposal_id), updated_deposit);
+
+        Ok(())
+    }
+
+    pub fn unlock_deposit(ico_id: u64, to: T::AccountId, value: T::TokenBalance, proposal_id: u64) -> Result {
+        let balance_to = Self::balance_of((ico_id, to.clone()));
+        let updated_balance_to = balance_to
+            .checked_add(&value)
+            .ok_or("overflow in adding balance")?;
+        let deposit = Self::locked_deposits((ico_id, proposal_id));
+        let updated_deposit = deposit
+            .checked_sub(&value)
+            .ok_or("overflow in subtracting deposit")?;
+
+        <Balances<T>>::insert((ico_id, to), updated_balance_to);
+        <LockedDeposits<T>>::insert((ico_id, proposal_id), updated_deposit);
+
+        Ok(())
+    }
 }
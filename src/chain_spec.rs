@@ -5,10 +5,11 @@ use std::marker::PhantomData;
 use substrate_service;
 use telemetry::TelemetryEndpoints;
 use turing_node_runtime::{
-    AccountId, BalancesConfig, ConsensusConfig, ContractConfig, CouncilSeatsConfig,
-    CouncilVotingConfig, DaoConfig, DaoTokenConfig, DemocracyConfig, ERC1400Config, ERC20Config,
-    ERC721Config, GenesisConfig, GrandpaConfig, IndicesConfig, Perbill, Permill, SessionConfig,
-    StakerStatus, StakingConfig, SudoConfig, TimestampConfig, TreasuryConfig,
+    AccountId, BalancesConfig, Clock, ConsensusConfig, ContractConfig, CouncilSeatsConfig,
+    CouncilVotingConfig, DaoConfig, DaoTokenConfig, DemocracyConfig, ERC1155Config,
+    ERC1400Config, ERC20Config, ERC721Config, GenesisConfig, GrandpaConfig, IndicesConfig,
+    Perbill, Permill, SessionConfig, StakerStatus, StakingConfig, SudoConfig, TimestampConfig,
+    TreasuryConfig,
 };
 
 // Note this is the URL for the telemetry server
@@ -252,9 +253,15 @@ fn testnet_genesis(
 			symbol: "ABT20".as_bytes().into(),
 		}),
 		erc721: Some(ERC721Config {
-			_genesis_phantom_data: PhantomData,
+			owner: account_key("Alice"),
 			name: "ABMatrix ERC721 Token".as_bytes().into(),
 			symbol: "ABT721".as_bytes().into(),
+			soulbound: false,
+		}),
+		erc1155: Some(ERC1155Config {
+			_genesis_phantom_data: PhantomData,
+			name: "ABMatrix ERC1155 Token".as_bytes().into(),
+			symbol: "ABT1155".as_bytes().into(),
 		}),
 		daotoken: Some(DaoTokenConfig{
 			total_supply: 21000000,
@@ -268,9 +275,20 @@ fn testnet_genesis(
 			min_proposal_deposit: 100,
 			min_quorum_divisor: 7,
 			min_proposal_debate_period: 2 * WEEKS,
+			max_proposal_debate_period: 8 * WEEKS,
 			quorum_havling_period: 25 * WEEKS,
 			execute_proposal_period: 10 * DAYS,
 			max_deposit_divisor: 100,
+			deposit_slash_percent: 50,
+			vote_lock_period: 1 * WEEKS,
+			grace_period: 1 * DAYS,
+			parameter_timelock: 2 * DAYS,
+			challenge_period: 3 * DAYS,
+			veto_divisor: 10,
+			reward_divisor: 10,
+			reward_claim_period: 2 * WEEKS,
+			// the periods above are block counts
+			clock: Clock::BlockNumber,
 		}),
 		erc1400: Some(ERC1400Config {
 			owner: account_key("Alice"),
@@ -445,9 +463,15 @@ fn turing_testnet_config_genesis() -> GenesisConfig {
 			symbol: "ABT20".as_bytes().into(),
 		}),
 		erc721: Some(ERC721Config {
-			_genesis_phantom_data: PhantomData,
+			owner: endowed_accounts[0].clone(),
 			name: "ABMatrix ERC721 Token".as_bytes().into(),
 			symbol: "ABT721".as_bytes().into(),
+			soulbound: false,
+		}),
+		erc1155: Some(ERC1155Config {
+			_genesis_phantom_data: PhantomData,
+			name: "ABMatrix ERC1155 Token".as_bytes().into(),
+			symbol: "ABT1155".as_bytes().into(),
 		}),
 		daotoken: Some(DaoTokenConfig{
 			total_supply: 21000000,
@@ -460,9 +484,20 @@ fn turing_testnet_config_genesis() -> GenesisConfig {
 			min_proposal_deposit: 100,
 			min_quorum_divisor: 7,
 			min_proposal_debate_period: 2 * WEEKS,
+			max_proposal_debate_period: 8 * WEEKS,
 			quorum_havling_period: 25 * WEEKS,
 			execute_proposal_period: 10 * DAYS,
 			max_deposit_divisor: 100,
+			deposit_slash_percent: 50,
+			vote_lock_period: 1 * WEEKS,
+			grace_period: 1 * DAYS,
+			parameter_timelock: 2 * DAYS,
+			challenge_period: 3 * DAYS,
+			veto_divisor: 10,
+			reward_divisor: 10,
+			reward_claim_period: 2 * WEEKS,
+			// the periods above are block counts
+			clock: Clock::BlockNumber,
 		}),
 		erc1400: Some(ERC1400Config {
 			owner: endowed_accounts[0].clone(),
@@ -0,0 +1,197 @@
+//! JSON-RPC methods of the node over the runtime APIs.
+//!
+//! The Substrate RPC server of this version can not be extended with custom methods, so
+//! these are served by a second HTTP server listening next to it.
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use jsonrpc_core::{Error, ErrorCode, IoHandler, Result};
+use jsonrpc_derive::rpc;
+use jsonrpc_http_server::{Server, ServerBuilder};
+use runtime_primitives::traits::ProvideRuntimeApi;
+use substrate_client as client;
+use substrate_service::FullClient;
+use turing_node_runtime::opaque::BlockId;
+use turing_node_runtime::{AccountId, DaoApi, Erc721Api, Hash, ProposalInfo};
+
+use crate::service::Factory;
+
+/// Queries over the `erc721` module, see `Erc721Api` in the runtime.
+#[rpc]
+pub trait Erc721Rpc {
+    /// Tokens owned by `account` from index `start`, at most `limit` items.
+    #[rpc(name = "erc721_tokensOfOwner")]
+    fn tokens_of_owner(&self, account: AccountId, start: u64, limit: u64) -> Result<Vec<Hash>>;
+
+    /// All the tokens from index `start`, at most `limit` items.
+    #[rpc(name = "erc721_tokensByIndex")]
+    fn tokens_by_index(&self, start: u64, limit: u64) -> Result<Vec<Hash>>;
+
+    #[rpc(name = "erc721_balanceOf")]
+    fn balance_of(&self, account: AccountId) -> Result<u64>;
+
+    #[rpc(name = "erc721_totalSupply")]
+    fn total_supply(&self) -> Result<u64>;
+
+    #[rpc(name = "erc721_ownerOf")]
+    fn owner_of(&self, token_id: Hash) -> Result<Option<AccountId>>;
+
+    #[rpc(name = "erc721_getApproved")]
+    fn get_approved(&self, token_id: Hash) -> Result<Option<AccountId>>;
+
+    #[rpc(name = "erc721_isApprovedForAll")]
+    fn is_approved_for_all(&self, owner: AccountId, operator: AccountId) -> Result<bool>;
+
+    /// Whether `account` is allowed to transfer `token_id`.
+    #[rpc(name = "erc721_isApproved")]
+    fn is_approved(&self, account: AccountId, token_id: Hash) -> Result<bool>;
+}
+
+/// Queries over the `dao` module, see `DaoApi` in the runtime.
+#[rpc]
+pub trait DaoRpc {
+    /// Proposal ids of the DAO from match `start`, at most `limit` items. `open`
+    /// selects the open or the closed proposals, `None` returns both.
+    #[rpc(name = "dao_proposals")]
+    fn proposals(&self, dao_id: u64, open: Option<bool>, start: u64, limit: u64) -> Result<Vec<u64>>;
+
+    /// The proposal with its tally, quorum requirement and time remaining.
+    #[rpc(name = "dao_proposal")]
+    fn proposal(&self, proposal_id: u64) -> Result<Option<ProposalInfo<AccountId, u128, u64, Hash>>>;
+
+    /// Votes of `account` from index `start`, at most `limit` items.
+    #[rpc(name = "dao_voterHistory")]
+    fn voter_history(&self, account: AccountId, start: u64, limit: u64) -> Result<Vec<(u64, Option<bool>, u128)>>;
+
+    #[rpc(name = "dao_blockedBy")]
+    fn blocked_by(&self, dao_id: u64, account: AccountId) -> Result<Option<u64>>;
+
+    #[rpc(name = "dao_votingPower")]
+    fn voting_power(&self, dao_id: u64, account: AccountId, topic: Option<u32>) -> Result<u128>;
+
+    #[rpc(name = "dao_delegationGraph")]
+    fn delegation_graph(&self, dao_id: u64, account: AccountId, topic: Option<u32>) -> Result<Vec<(AccountId, AccountId)>>;
+
+    #[rpc(name = "dao_treasuryAccount")]
+    fn treasury_account(&self, dao_id: u64) -> Result<AccountId>;
+
+    #[rpc(name = "dao_rewardAccount")]
+    fn reward_account(&self, dao_id: u64) -> Result<AccountId>;
+}
+
+/// Answers the queries at the best block of the client.
+#[derive(Clone)]
+pub struct NodeRpc {
+    client: Arc<FullClient<Factory>>,
+}
+
+impl NodeRpc {
+    fn best_block(&self) -> Result<BlockId> {
+        let info = self.client.info().map_err(client_error)?;
+        Ok(BlockId::hash(info.chain.best_hash))
+    }
+}
+
+fn client_error(e: client::error::Error) -> Error {
+    Error {
+        code: ErrorCode::InternalError,
+        message: format!("{}", e),
+        data: None,
+    }
+}
+
+impl Erc721Rpc for NodeRpc {
+    fn tokens_of_owner(&self, account: AccountId, start: u64, limit: u64) -> Result<Vec<Hash>> {
+        let at = self.best_block()?;
+        self.client.runtime_api().tokens_of_owner(&at, account, start, limit).map_err(client_error)
+    }
+
+    fn tokens_by_index(&self, start: u64, limit: u64) -> Result<Vec<Hash>> {
+        let at = self.best_block()?;
+        self.client.runtime_api().tokens_by_index(&at, start, limit).map_err(client_error)
+    }
+
+    fn balance_of(&self, account: AccountId) -> Result<u64> {
+        let at = self.best_block()?;
+        self.client.runtime_api().balance_of(&at, account).map_err(client_error)
+    }
+
+    fn total_supply(&self) -> Result<u64> {
+        let at = self.best_block()?;
+        self.client.runtime_api().total_supply(&at).map_err(client_error)
+    }
+
+    fn owner_of(&self, token_id: Hash) -> Result<Option<AccountId>> {
+        let at = self.best_block()?;
+        self.client.runtime_api().owner_of(&at, token_id).map_err(client_error)
+    }
+
+    fn get_approved(&self, token_id: Hash) -> Result<Option<AccountId>> {
+        let at = self.best_block()?;
+        self.client.runtime_api().get_approved(&at, token_id).map_err(client_error)
+    }
+
+    fn is_approved_for_all(&self, owner: AccountId, operator: AccountId) -> Result<bool> {
+        let at = self.best_block()?;
+        self.client.runtime_api().is_approved_for_all(&at, owner, operator).map_err(client_error)
+    }
+
+    fn is_approved(&self, account: AccountId, token_id: Hash) -> Result<bool> {
+        let at = self.best_block()?;
+        self.client.runtime_api().is_approved(&at, account, token_id).map_err(client_error)
+    }
+}
+
+impl DaoRpc for NodeRpc {
+    fn proposals(&self, dao_id: u64, open: Option<bool>, start: u64, limit: u64) -> Result<Vec<u64>> {
+        let at = self.best_block()?;
+        self.client.runtime_api().proposals(&at, dao_id, open, start, limit).map_err(client_error)
+    }
+
+    fn proposal(&self, proposal_id: u64) -> Result<Option<ProposalInfo<AccountId, u128, u64, Hash>>> {
+        let at = self.best_block()?;
+        self.client.runtime_api().proposal(&at, proposal_id).map_err(client_error)
+    }
+
+    fn voter_history(&self, account: AccountId, start: u64, limit: u64) -> Result<Vec<(u64, Option<bool>, u128)>> {
+        let at = self.best_block()?;
+        self.client.runtime_api().voter_history(&at, account, start, limit).map_err(client_error)
+    }
+
+    fn blocked_by(&self, dao_id: u64, account: AccountId) -> Result<Option<u64>> {
+        let at = self.best_block()?;
+        self.client.runtime_api().blocked_by(&at, dao_id, account).map_err(client_error)
+    }
+
+    fn voting_power(&self, dao_id: u64, account: AccountId, topic: Option<u32>) -> Result<u128> {
+        let at = self.best_block()?;
+        self.client.runtime_api().voting_power(&at, dao_id, account, topic).map_err(client_error)
+    }
+
+    fn delegation_graph(&self, dao_id: u64, account: AccountId, topic: Option<u32>) -> Result<Vec<(AccountId, AccountId)>> {
+        let at = self.best_block()?;
+        self.client.runtime_api().delegation_graph(&at, dao_id, account, topic).map_err(client_error)
+    }
+
+    fn treasury_account(&self, dao_id: u64) -> Result<AccountId> {
+        let at = self.best_block()?;
+        self.client.runtime_api().treasury_account(&at, dao_id).map_err(client_error)
+    }
+
+    fn reward_account(&self, dao_id: u64) -> Result<AccountId> {
+        let at = self.best_block()?;
+        self.client.runtime_api().reward_account(&at, dao_id).map_err(client_error)
+    }
+}
+
+/// Start serving the node RPC over HTTP on `addr`, it stops when the returned server is dropped.
+pub fn start_http(addr: &SocketAddr, client: Arc<FullClient<Factory>>) -> io::Result<Server> {
+    let rpc = NodeRpc { client };
+    let mut io = IoHandler::new();
+    io.extend_with(Erc721Rpc::to_delegate(rpc.clone()));
+    io.extend_with(DaoRpc::to_delegate(rpc));
+
+    ServerBuilder::new(io).threads(1).start_http(addr)
+}
@@ -22,6 +22,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use consensus::{import_queue, start_aura, AuraImportQueue, NothingExtra, SlotDuration};
+use futures::Future;
 use grandpa;
 use inherents::InherentDataProviders;
 use log::info;
@@ -138,6 +139,17 @@ construct_service_factory! {
                     service.on_exit(),
                 )?);
 
+                // the node RPC listens on the port following the one of the Substrate RPC
+                if let Some(mut addr) = service.config.rpc_http {
+                    let port = addr.port().checked_add(1)
+                        .ok_or_else(|| format!("No port follows the Substrate RPC port {}", addr.port()))?;
+                    addr.set_port(port);
+                    let server = crate::rpc::start_http(&addr, service.client())
+                        .map_err(|e| format!("Unable to start the node RPC on {}: {}", addr, e))?;
+                    info!("Node RPC listening on {}", addr);
+                    executor.spawn(service.on_exit().map(move |_| drop(server)));
+                }
+
                 Ok(service)
             }
         },